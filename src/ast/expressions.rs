@@ -1,5 +1,10 @@
+use super::statements::Function;
 use crate::syntax::token::Literal as TokenLiteral;
 use crate::syntax::token::Token;
+use std::cell::Cell;
+
+// how many scopes out from the current one a name was declared, filled in by the resolver. None means global.
+pub type Depth = Cell<Option<usize>>;
 
 #[macro_export]
 macro_rules! define_ast {
@@ -12,10 +17,22 @@ macro_rules! define_ast {
             )*
         }
 
+        impl $enum_name {
+            pub fn span(&self) -> $crate::syntax::token::Span {
+                match self {
+                    $(
+                      $enum_name::$variant(node) => node.span,
+                    )*
+                }
+            }
+        }
+
+        // every node records the source it was parsed from.
         $(
             #[derive(Debug)]
             pub struct $variant {
                 $(pub $field: $type,)*
+                pub span: $crate::syntax::token::Span,
             }
         )*
     };
@@ -41,7 +58,84 @@ define_ast! {
         right: Box<Expr>
     },
     Variable {
+        name: Token,
+        depth: Depth
+    },
+    Assign {
+        name: Token,
+        operator: Option<Token>, // set for compound assignments like +=, typed as the plain operator.
+        value: Box<Expr>,
+        postfix: bool, // x++ and x-- give back the value from before the update.
+        depth: Depth
+    },
+    Logical {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>
+    },
+    Conditional {
+        condition: Box<Expr>,
+        question: Token,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>
+    },
+    Coalesce {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>
+    },
+    Call {
+        callee: Box<Expr>,
+        paren: Token,
+        arguments: Vec<Expr>,
+        named: Vec<(Token, Expr)> // name: value arguments, always after the positional ones.
+    },
+    Get {
+        object: Box<Expr>,
         name: Token
+    },
+    Set {
+        object: Box<Expr>,
+        name: Token,
+        operator: Option<Token>,
+        value: Box<Expr>,
+        postfix: bool
+    },
+    This {
+        keyword: Token,
+        depth: Depth
+    },
+    Super {
+        keyword: Token,
+        method: Token,
+        depth: Depth
+    },
+    List {
+        bracket: Token,
+        elements: Vec<Expr>
+    },
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>
+    },
+    IndexSet {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        operator: Option<Token>,
+        value: Box<Expr>,
+        postfix: bool
+    },
+    Map {
+        brace: Token,
+        entries: Vec<(Expr, Expr)>
+    },
+    Interpolation {
+        parts: Vec<Expr>
+    },
+    Lambda {
+        function: Function // named "anonymous", as there is nothing to bind a name to.
     }
 }
 
@@ -1,8 +1,13 @@
 pub mod expressions;
 pub mod parser;
+pub mod resolver;
 pub mod statements;
 use expressions::Expr;
 
+// how deeply brackets, blocks, unary operators and statement bodies may nest, which bounds how far
+// the parser recurses. Chains like 1 + 1 + 1 are parsed in a loop and don't count towards it.
+pub(crate) const MAX_NESTING: usize = 256;
+
 // impl Expr {
 //     pub fn ast_printer(&self) {
 //         match self {
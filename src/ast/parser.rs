@@ -2,17 +2,33 @@ use crate::errors::parse_error::ParseError;
 use crate::syntax::token::Literal as LiteralToken;
 
 use super::{
-    expressions::{Binary, Grouping, Literal, Unary, Variable},
-    statements::{Expression, Import, Print, Stmt},
-    Expr,
+    expressions::{
+        Assign, Binary, Call, Coalesce, Conditional, Get, Grouping, Index, IndexSet, Interpolation,
+        Lambda, List, Literal, Logical, Map, Set, Super, This, Unary, Variable,
+    },
+    statements::{
+        Block, Break, Class, Continue, Expression, Function, If, Import, Parameter, Print, Return,
+        Stmt, Throw, Try, While,
+    },
+    Expr, MAX_NESTING,
 };
-use crate::syntax::token::{Token, TokenType};
+use crate::syntax::token::{Span, Token, TokenType};
+use std::cell::Cell;
+use std::rc::Rc;
+
+const MAX_ARGUMENTS: usize = 255;
 
 #[derive(Debug)]
 pub struct Parser<'a> {
     tokens: Vec<Token>,
     current: usize,
     pub errors: Vec<ParseError<'a>>,
+    synchronised: usize, // number of errors already recovered from, so enclosing blocks dont synchronise twice.
+    loop_depth: usize, // how many loops enclose the current statement, break and continue are only valid inside one.
+    depth: usize, // how many brackets, blocks, unary operators and statement bodies enclose the current token.
+    statement_start: usize, // where the top level statement being parsed began.
+    nesting_error: Option<usize>, // the number of errors up to and including the one for nesting too deeply.
+    held_back: Vec<Token>, // the tokens after a statement that nested too deeply, set aside while the parser unwinds.
 }
 
 impl<'a> Parser<'a> {
@@ -21,59 +37,178 @@ impl<'a> Parser<'a> {
             tokens,
             current: 0,
             errors: Vec::new(),
+            synchronised: 0,
+            loop_depth: 0,
+            depth: 0,
+            statement_start: 0,
+            nesting_error: None,
+            held_back: Vec::new(),
         }
     }
 
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut statements: Vec<Stmt> = Vec::new();
         while !self.is_at_end() {
+            self.statement_start = self.current;
             statements.push(self.declaration());
+            // the statement cut short by nesting too deeply reports errors that are only noise,
+            // parsing picks up again with the statement after it.
+            if let Some(errors) = self.nesting_error.take() {
+                self.errors.truncate(errors);
+                self.synchronised = errors;
+                self.tokens.pop();
+                self.current = self.tokens.len();
+                self.tokens.append(&mut self.held_back);
+            }
         }
         statements
     }
 
     fn declaration(&mut self) -> Stmt {
-        let before = self.errors.len();
-        if self.match_types(vec![TokenType::Let]) {
-            let stmt = self.var_declaration();
-            if before < self.errors.len() {
-                // if error occured in above operation synchronise tokens and keep going.
-                self.synchonise();
-            }
-            stmt
+        let stmt = if self.match_types(vec![TokenType::Let]) {
+            self.var_declaration("Expected ; after variable declaration")
+        } else if self.check(TokenType::Gorilla) && self.check_at(1, TokenType::Identifier) {
+            // a gorilla without a name is a lambda, which is parsed as an expression statement.
+            let keyword = self.advance();
+            Stmt::Function(self.function(keyword.span()))
+        } else if self.match_types(vec![TokenType::Jungle]) {
+            self.class_declaration()
         } else {
             self.statement()
+        };
+        if self.synchronised < self.errors.len() {
+            // if error occured in above operation synchronise tokens and keep going.
+            self.synchronised = self.errors.len();
+            self.synchonise();
         }
+        stmt
     }
 
-    fn var_declaration(&mut self) -> Stmt {
+    // msg is reported when the terminating ; is missing, for loops use their own so it points at the clause.
+    fn var_declaration(&mut self, msg: &'a str) -> Stmt {
+        let start = self.previous().span();
         let name = self.consume(TokenType::Identifier, "Expected an identifier");
         let mut initialiser = Expr::Literal(Literal {
             value: LiteralToken::None,
+            span: self.previous().span(),
         });
         if self.match_types(vec![TokenType::Equal]) {
             initialiser = self.expression();
         }
-        self.consume(
-            TokenType::Semicolon,
-            "Expected ; after variable declaration",
-        );
+        self.consume(TokenType::Semicolon, msg);
         if let Some(var) = name {
-            return Stmt::Let(super::statements::Let {
+            Stmt::Let(super::statements::Let {
                 name: var,
                 initialiser,
-            });
+                span: self.span_from(start),
+            })
         } else {
-            return Stmt::Let(super::statements::Let {
-                name: Token {
-                    token_type: TokenType::Eof,
-                    lexeme: "".to_string(),
-                    literal: LiteralToken::None,
-                    line: 0,
-                },
+            Stmt::Let(super::statements::Let {
+                name: Token::new(
+                    TokenType::Eof,
+                    "".to_string(),
+                    LiteralToken::None,
+                    Span::default(),
+                ),
                 initialiser,
-            });
+                span: self.span_from(start),
+            })
+        }
+    }
+
+    fn class_declaration(&mut self) -> Stmt {
+        let start = self.previous().span();
+        let name = self
+            .consume(TokenType::Identifier, "Expected Jungle name.")
+            .unwrap_or_else(|| self.previous());
+        let mut superclass = None;
+        if self.match_types(vec![TokenType::Less]) {
+            if let Some(parent) =
+                self.consume(TokenType::Identifier, "Expected parent Jungle name.")
+            {
+                superclass = Some(Expr::Variable(Variable {
+                    span: parent.span(),
+                    name: parent,
+                    depth: Cell::new(None),
+                }));
+            }
+        }
+        self.consume(TokenType::LeftBrace, "Expected { before Jungle body.");
+
+        let mut methods: Vec<Function> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let start = self.peek().span();
+            methods.push(self.function(start));
+        }
+        self.consume(TokenType::RightBrace, "Expected } after Jungle body.");
+
+        Stmt::Class(Class {
+            name,
+            superclass,
+            methods,
+            span: self.span_from(start),
+        })
+    }
+
+    // shared by gorilla declarations and Jungle methods, which are written without the keyword.
+    // start is where the declaration began, the keyword or the method name.
+    fn function(&mut self, start: Span) -> Function {
+        let name = self
+            .consume(TokenType::Identifier, "Expected gorilla name.")
+            .unwrap_or_else(|| self.previous());
+        self.consume(TokenType::LeftParen, "Expected ( after gorilla name.");
+        let (params, rest) = self.parameters();
+        self.consume(TokenType::LeftBrace, "Expected { before gorilla body.");
+        let body = self.function_body();
+
+        Function {
+            name,
+            params: Rc::new(params),
+            rest,
+            body: Rc::new(body),
+            span: self.span_from(start),
+        }
+    }
+
+    // the parameter list after the opening (, up to and including the closing ).
+    // parameters may have a default, like b = 10, and the last one may be a rest parameter, like ...args.
+    fn parameters(&mut self) -> (Vec<Parameter>, Option<Token>) {
+        let mut params: Vec<Parameter> = Vec::new();
+        let mut rest = None;
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.report(self.peek(), "Can't have more than 255 parameters.");
+                }
+                if self.match_types(vec![TokenType::Ellipsis]) {
+                    rest = self.consume(TokenType::Identifier, "Expected parameter name after ...");
+                    if self.check(TokenType::Comma) {
+                        self.report(self.peek(), "The rest parameter has to be the last one.");
+                    }
+                } else if let Some(name) =
+                    self.consume(TokenType::Identifier, "Expected parameter name.")
+                {
+                    let default = match self.match_types(vec![TokenType::Equal]) {
+                        true => Some(self.expression()),
+                        false => None,
+                    };
+                    params.push(Parameter { name, default });
+                }
+                if !self.match_types(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
         }
+        self.consume(TokenType::RightParen, "Expected ) after parameters.");
+        (params, rest)
+    }
+
+    fn function_body(&mut self) -> Vec<Stmt> {
+        // a loop around the declaration does not make break valid inside the body.
+        let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.block();
+        self.loop_depth = enclosing_loop_depth;
+        body
     }
 
     fn statement(&mut self) -> Stmt {
@@ -81,41 +216,430 @@ impl<'a> Parser<'a> {
             self.print_statement()
         } else if self.match_types(vec![TokenType::Import]) {
             self.import_statement()
+        } else if self.match_types(vec![TokenType::LeftBrace]) {
+            let start = self.previous().span();
+            Stmt::Block(Block {
+                statements: self.block(),
+                span: self.span_from(start),
+            })
+        } else if self.match_types(vec![TokenType::If]) {
+            self.if_statement()
+        } else if self.match_types(vec![TokenType::While]) {
+            self.while_statement()
+        } else if self.match_types(vec![TokenType::For]) {
+            self.for_statement()
+        } else if self.match_types(vec![TokenType::Break, TokenType::Continue]) {
+            self.loop_control_statement()
+        } else if self.match_types(vec![TokenType::Return]) {
+            self.return_statement()
+        } else if self.match_types(vec![TokenType::Try]) {
+            self.try_statement()
+        } else if self.match_types(vec![TokenType::Throw]) {
+            self.throw_statement()
         } else {
             self.expression_statement()
         }
     }
     fn import_statement(&mut self) -> Stmt {
+        let keyword = self.previous();
         let file_name_token = self.consume(TokenType::String, "Expected file name");
         self.consume(
             TokenType::Semicolon,
             "Expected ; after file name in import statement",
         );
+        let span = self.span_from(keyword.span());
         if let Some(token) = file_name_token {
             Stmt::Import(Import {
+                keyword,
                 file_name: token.lexeme,
+                span,
             })
         } else {
             Stmt::Print(Print {
                 expression: Expr::Literal(Literal {
                     value: LiteralToken::None,
+                    span,
                 }),
+                span,
+            })
+        }
+    }
+
+    // else binds to the nearest if, so else if chains fall out of parsing the else branch as a statement.
+    fn if_statement(&mut self) -> Stmt {
+        let start = self.previous().span();
+        self.consume(TokenType::LeftParen, "Expected ( after if.");
+        let condition = self.expression();
+        self.consume(TokenType::RightParen, "Expected ) after if condition.");
+        let then_branch = Box::new(self.nested(Self::statement));
+        let mut else_branch = None;
+        if self.match_types(vec![TokenType::Else]) {
+            else_branch = Some(Box::new(self.nested(Self::statement)));
+        }
+        Stmt::If(If {
+            condition,
+            then_branch,
+            else_branch,
+            span: self.span_from(start),
+        })
+    }
+
+    fn while_statement(&mut self) -> Stmt {
+        let start = self.previous().span();
+        self.consume(TokenType::LeftParen, "Expected ( after while.");
+        let condition = self.expression();
+        self.consume(TokenType::RightParen, "Expected ) after while condition.");
+        self.loop_depth += 1;
+        let body = Box::new(self.nested(Self::statement));
+        self.loop_depth -= 1;
+        Stmt::While(While {
+            condition,
+            body,
+            increment: None,
+            span: self.span_from(start),
+        })
+    }
+
+    // desugars for (init; cond; incr) body into { init; while (cond) body } with incr run after every iteration.
+    fn for_statement(&mut self) -> Stmt {
+        let start = self.previous().span();
+        self.consume(TokenType::LeftParen, "Expected ( after for.");
+        let initialiser = if self.match_types(vec![TokenType::Semicolon]) {
+            None
+        } else if self.match_types(vec![TokenType::Let]) {
+            Some(self.var_declaration("Expected ; after for loop initialiser."))
+        } else {
+            let expression = self.expression();
+            self.consume(
+                TokenType::Semicolon,
+                "Expected ; after for loop initialiser.",
+            );
+            Some(Stmt::Expression(Expression {
+                span: self.span_from(expression.span()),
+                expression,
+            }))
+        };
+
+        let condition = if self.check(TokenType::Semicolon) {
+            Expr::Literal(Literal {
+                value: LiteralToken::Bool(true),
+                span: self.peek().span(),
             })
+        } else {
+            self.expression()
+        };
+        self.consume(TokenType::Semicolon, "Expected ; after for loop condition.");
+
+        let increment = if self.check(TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression())
+        };
+        self.consume(TokenType::RightParen, "Expected ) after for loop clauses.");
+
+        self.loop_depth += 1;
+        let body = Box::new(self.nested(Self::statement));
+        self.loop_depth -= 1;
+
+        // appending the increment to the body would make continue skip it, so the loop runs it itself.
+        let span = self.span_from(start);
+        let while_loop = Stmt::While(While {
+            condition,
+            body,
+            increment,
+            span,
+        });
+        match initialiser {
+            // the block keeps the loop variable scoped to the loop.
+            Some(initialiser) => Stmt::Block(Block {
+                statements: vec![initialiser, while_loop],
+                span,
+            }),
+            None => while_loop,
+        }
+    }
+
+    fn loop_control_statement(&mut self) -> Stmt {
+        let keyword = self.previous();
+        if self.loop_depth == 0 {
+            self.report(
+                keyword.clone(),
+                "Can only use break and continue inside a loop.",
+            );
+        }
+        self.consume(TokenType::Semicolon, "Expected ; after break or continue.");
+        let span = self.span_from(keyword.span());
+        match keyword.token_type {
+            TokenType::Break => Stmt::Break(Break { keyword, span }),
+            _ => Stmt::Continue(Continue { keyword, span }),
+        }
+    }
+
+    fn return_statement(&mut self) -> Stmt {
+        let keyword = self.previous();
+        let value = if self.check(TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression())
+        };
+        self.consume(TokenType::Semicolon, "Expected ; after return value.");
+        Stmt::Return(Return {
+            span: self.span_from(keyword.span()),
+            keyword,
+            value,
+        })
+    }
+
+    // a try needs a catch, a finally or both.
+    fn try_statement(&mut self) -> Stmt {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftBrace, "Expected { after try.");
+        let body = self.block();
+        let mut catch_name = None;
+        let mut catch_body = None;
+        if self.match_types(vec![TokenType::Catch]) {
+            if self.match_types(vec![TokenType::LeftParen]) {
+                catch_name =
+                    self.consume(TokenType::Identifier, "Expected error name after catch (.");
+                self.consume(TokenType::RightParen, "Expected ) after catch error name.");
+            }
+            self.consume(TokenType::LeftBrace, "Expected { after catch.");
+            catch_body = Some(self.block());
+        }
+        let mut finally_body = None;
+        if self.match_types(vec![TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expected { after finally.");
+            finally_body = Some(self.block());
+        }
+        if catch_body.is_none() && finally_body.is_none() {
+            self.report(
+                keyword.clone(),
+                "Expected catch or finally after try block.",
+            );
         }
+        Stmt::Try(Try {
+            span: self.span_from(keyword.span()),
+            keyword,
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        })
+    }
+
+    fn throw_statement(&mut self) -> Stmt {
+        let keyword = self.previous();
+        let value = self.expression();
+        self.consume(TokenType::Semicolon, "Expected ; after thrown value.");
+        Stmt::Throw(Throw {
+            span: self.span_from(keyword.span()),
+            keyword,
+            value,
+        })
+    }
+
+    // assumes the opening { has already been consumed.
+    fn block(&mut self) -> Vec<Stmt> {
+        let mut statements: Vec<Stmt> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.nested(Self::declaration));
+        }
+        self.consume(TokenType::RightBrace, "Expected } after block.");
+        statements
     }
 
     fn print_statement(&mut self) -> Stmt {
+        let start = self.previous().span();
         let value = self.expression();
         self.consume(TokenType::Semicolon, "Expected ; after value.");
-        Stmt::Print(Print { expression: value })
+        Stmt::Print(Print {
+            expression: value,
+            span: self.span_from(start),
+        })
     }
     fn expression_statement(&mut self) -> Stmt {
         let expr = self.expression();
         self.consume(TokenType::Semicolon, "Expected ; after expression");
-        Stmt::Expression(Expression { expression: expr })
+        Stmt::Expression(Expression {
+            span: self.span_from(expr.span()),
+            expression: expr,
+        })
     }
     fn expression(&mut self) -> Expr {
-        self.equality()
+        self.nested(Self::assignment)
+    }
+
+    // assignment is right associative so a = b = c assigns c to both.
+    fn assignment(&mut self) -> Expr {
+        let expr = self.conditional();
+
+        if self.match_types(vec![
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::PercentEqual,
+        ]) {
+            let equals = self.previous();
+            let operator = compound_operator(&equals);
+            let value = self.nested(Self::assignment);
+            let span = expr.span().to(value.span());
+            return match expr {
+                Expr::Variable(var) => Expr::Assign(Assign {
+                    name: var.name,
+                    operator,
+                    value: Box::new(value),
+                    postfix: false,
+                    depth: Cell::new(None),
+                    span,
+                }),
+                Expr::Get(get) => Expr::Set(Set {
+                    object: get.object,
+                    name: get.name,
+                    operator,
+                    value: Box::new(value),
+                    postfix: false,
+                    span,
+                }),
+                Expr::Index(index) => Expr::IndexSet(IndexSet {
+                    object: index.object,
+                    bracket: index.bracket,
+                    index: index.index,
+                    operator,
+                    value: Box::new(value),
+                    postfix: false,
+                    span,
+                }),
+                _ => {
+                    self.report(equals, "Invalid assignment target.");
+                    expr
+                }
+            };
+        }
+
+        expr
+    }
+
+    // ++x and x++ are sugar for x += 1 on anything that can be assigned to, and likewise for --.
+    fn increment(&mut self, operator: Token, target: Expr, postfix: bool) -> Expr {
+        let span = if postfix {
+            target.span().to(operator.span())
+        } else {
+            operator.span().to(target.span())
+        };
+        let compound = compound_operator(&operator);
+        let one = Box::new(Expr::Literal(Literal {
+            value: LiteralToken::Int(1),
+            span: operator.span(),
+        }));
+        match target {
+            Expr::Variable(var) => Expr::Assign(Assign {
+                name: var.name,
+                operator: compound,
+                value: one,
+                postfix,
+                depth: Cell::new(None),
+                span,
+            }),
+            Expr::Get(get) => Expr::Set(Set {
+                object: get.object,
+                name: get.name,
+                operator: compound,
+                value: one,
+                postfix,
+                span,
+            }),
+            Expr::Index(index) => Expr::IndexSet(IndexSet {
+                object: index.object,
+                bracket: index.bracket,
+                index: index.index,
+                operator: compound,
+                value: one,
+                postfix,
+                span,
+            }),
+            _ => {
+                self.report(operator, "Invalid increment target.");
+                target
+            }
+        }
+    }
+
+    // CONDITIONAL EXPRESSIONS:
+
+    // right associative, so a ? b : c ? d : e picks between d and e when a is falsy.
+    fn conditional(&mut self) -> Expr {
+        let condition = self.coalesce();
+
+        if self.match_types(vec![TokenType::Question]) {
+            let question = self.previous();
+            let then_branch = self.expression();
+            self.consume(TokenType::Colon, "Expected : after the ? branch.");
+            let else_branch = self.nested(Self::conditional);
+            return Expr::Conditional(Conditional {
+                span: condition.span().to(else_branch.span()),
+                condition: Box::new(condition),
+                question,
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+
+        condition
+    }
+
+    fn coalesce(&mut self) -> Expr {
+        let mut expr = self.or();
+
+        while self.match_types(vec![TokenType::QuestionQuestion]) {
+            let operator = self.previous();
+            let right = self.or();
+            expr = Expr::Coalesce(Coalesce {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        expr
+    }
+
+    // LOGICAL EXPRESSIONS:
+
+    fn or(&mut self) -> Expr {
+        let mut expr = self.and();
+
+        while self.match_types(vec![TokenType::Or]) {
+            let operator = self.previous();
+            let right = self.and();
+            expr = Expr::Logical(Logical {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        expr
+    }
+
+    fn and(&mut self) -> Expr {
+        let mut expr = self.equality();
+
+        while self.match_types(vec![TokenType::And]) {
+            let operator = self.previous();
+            let right = self.equality();
+            expr = Expr::Logical(Logical {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        expr
     }
 
     // BINARY EXPRESSIONS:
@@ -127,6 +651,7 @@ impl<'a> Parser<'a> {
             let operator = self.previous();
             let right = self.comparison();
             expr = Expr::Binary(super::expressions::Binary {
+                span: expr.span().to(right.span()),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -137,7 +662,7 @@ impl<'a> Parser<'a> {
     }
 
     fn comparison(&mut self) -> Expr {
-        let mut expr = self.term();
+        let mut expr = self.bit_or();
 
         while self.match_types(vec![
             TokenType::Greater,
@@ -145,9 +670,75 @@ impl<'a> Parser<'a> {
             TokenType::Less,
             TokenType::LessEqual,
         ]) {
+            let operator = self.previous();
+            let right = self.bit_or();
+            expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        expr
+    }
+
+    // the bitwise operators bind tighter than comparisons, so a & mask == 0 means (a & mask) == 0.
+    fn bit_or(&mut self) -> Expr {
+        let mut expr = self.bit_xor();
+
+        while self.match_types(vec![TokenType::Pipe]) {
+            let operator = self.previous();
+            let right = self.bit_xor();
+            expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        expr
+    }
+
+    fn bit_xor(&mut self) -> Expr {
+        let mut expr = self.bit_and();
+
+        while self.match_types(vec![TokenType::Caret]) {
+            let operator = self.previous();
+            let right = self.bit_and();
+            expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        expr
+    }
+
+    fn bit_and(&mut self) -> Expr {
+        let mut expr = self.shift();
+
+        while self.match_types(vec![TokenType::Ampersand]) {
+            let operator = self.previous();
+            let right = self.shift();
+            expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        expr
+    }
+
+    fn shift(&mut self) -> Expr {
+        let mut expr = self.term();
+
+        while self.match_types(vec![TokenType::LessLess, TokenType::GreaterGreater]) {
             let operator = self.previous();
             let right = self.term();
             expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -163,6 +754,7 @@ impl<'a> Parser<'a> {
             let operator = self.previous();
             let right = self.factor();
             expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -174,10 +766,16 @@ impl<'a> Parser<'a> {
 
     fn factor(&mut self) -> Expr {
         let mut expr = self.unary();
-        while self.match_types(vec![TokenType::Slash, TokenType::Star]) {
+        while self.match_types(vec![
+            TokenType::Slash,
+            TokenType::Star,
+            TokenType::Percent,
+            TokenType::TildeSlash,
+        ]) {
             let operator = self.previous();
             let right = self.unary();
             expr = Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
@@ -190,45 +788,193 @@ impl<'a> Parser<'a> {
     // UNARY AND PRIMARY EXPRESSIONS:
 
     fn unary(&mut self) -> Expr {
+        if self.match_types(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous();
+            let target = self.nested(Self::unary);
+            return self.increment(operator, target, false);
+        }
         if self.match_types(vec![TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous();
-            let right = self.unary();
+            let right = self.nested(Self::unary);
             return Expr::Unary(Unary {
+                span: operator.span().to(right.span()),
                 operator,
                 right: Box::new(right),
             });
         }
 
-        self.primary()
+        self.power()
+    }
+
+    // ** binds tighter than a unary minus on its left, so -2 ** 2 is -4, and is right associative,
+    // so 2 ** 3 ** 2 is 2 ** 9. The exponent may carry its own sign, as in 2 ** -1.
+    fn power(&mut self) -> Expr {
+        let expr = self.postfix();
+
+        if self.match_types(vec![TokenType::StarStar]) {
+            let operator = self.previous();
+            let right = self.nested(Self::unary);
+            return Expr::Binary(Binary {
+                span: expr.span().to(right.span()),
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+        expr
+    }
+
+    fn postfix(&mut self) -> Expr {
+        let expr = self.call();
+
+        if self.match_types(vec![TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous();
+            return self.increment(operator, expr, true);
+        }
+        expr
+    }
+
+    fn call(&mut self) -> Expr {
+        let mut expr = self.primary();
+
+        loop {
+            if self.match_types(vec![TokenType::LeftParen]) {
+                expr = self.finish_call(expr);
+            } else if self.match_types(vec![TokenType::Dot]) {
+                let name = self
+                    .consume(TokenType::Identifier, "Expected property name after \".\".")
+                    .unwrap_or_else(|| self.previous());
+                expr = Expr::Get(Get {
+                    span: expr.span().to(name.span()),
+                    object: Box::new(expr),
+                    name,
+                });
+            } else if self.match_types(vec![TokenType::LeftBracket]) {
+                let bracket = self.previous();
+                let index = self.expression();
+                self.consume(TokenType::RightBracket, "Expected ] after index.");
+                expr = Expr::Index(Index {
+                    span: self.span_from(expr.span()),
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                });
+            } else {
+                break;
+            }
+        }
+
+        expr
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Expr {
+        let mut arguments: Vec<Expr> = Vec::new();
+        let mut named: Vec<(Token, Expr)> = Vec::new();
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if arguments.len() + named.len() >= MAX_ARGUMENTS {
+                    self.report(self.peek(), "Can't have more than 255 arguments.");
+                }
+                if self.check(TokenType::Identifier) && self.check_at(1, TokenType::Colon) {
+                    let name = self.advance();
+                    self.advance();
+                    named.push((name, self.expression()));
+                } else {
+                    if !named.is_empty() {
+                        self.report(
+                            self.peek(),
+                            "Positional arguments can't follow named arguments.",
+                        );
+                    }
+                    arguments.push(self.expression());
+                }
+                if !self.match_types(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        let paren = self
+            .consume(TokenType::RightParen, "Expected ) after arguments.")
+            .unwrap_or_else(|| self.previous());
+        Expr::Call(Call {
+            span: callee.span().to(paren.span()),
+            callee: Box::new(callee),
+            paren,
+            arguments,
+            named,
+        })
     }
 
     fn primary(&mut self) -> Expr {
         if self.match_types(vec![TokenType::False]) {
-            return Expr::Literal(Literal {
+            Expr::Literal(Literal {
                 value: LiteralToken::Bool(false),
-            });
+                span: self.previous().span(),
+            })
         } else if self.match_types(vec![TokenType::True]) {
-            return Expr::Literal(Literal {
+            Expr::Literal(Literal {
                 value: LiteralToken::Bool(true),
-            });
+                span: self.previous().span(),
+            })
         } else if self.match_types(vec![TokenType::Null]) {
-            return Expr::Literal(Literal {
+            Expr::Literal(Literal {
                 value: LiteralToken::None,
-            });
+                span: self.previous().span(),
+            })
         } else if self.match_types(vec![TokenType::Number, TokenType::String]) {
-            return Expr::Literal(Literal {
+            Expr::Literal(Literal {
                 value: self.previous().literal,
-            });
+                span: self.previous().span(),
+            })
+        } else if self.match_types(vec![TokenType::Gorilla]) {
+            let keyword = self.previous();
+            self.consume(TokenType::LeftParen, "Expected ( after gorilla.");
+            let (params, rest) = self.parameters();
+            self.consume(TokenType::LeftBrace, "Expected { before gorilla body.");
+            let body = self.function_body();
+            let span = self.span_from(keyword.span());
+            lambda(&keyword, params, rest, body, span)
+        } else if self.check(TokenType::LeftParen) && self.is_arrow_function() {
+            self.arrow_function()
         } else if self.match_types(vec![TokenType::LeftParen]) {
+            let start = self.previous().span();
             let expr = self.expression();
             self.consume(TokenType::RightParen, "Expected \")\" after expression.");
-            return Expr::Grouping(Grouping {
+            Expr::Grouping(Grouping {
                 expression: Box::new(expr),
-            });
+                span: self.span_from(start),
+            })
         } else if self.match_types(vec![TokenType::Identifier]) {
-            return Expr::Variable(Variable {
+            Expr::Variable(Variable {
                 name: self.previous(),
-            });
+                depth: Cell::new(None),
+                span: self.previous().span(),
+            })
+        } else if self.match_types(vec![TokenType::Interpolation]) {
+            self.interpolation()
+        } else if self.match_types(vec![TokenType::LeftBracket]) {
+            self.list()
+        } else if self.match_types(vec![TokenType::LeftBrace]) {
+            // statements starting with { are blocks, so a brace only reaches here in expression position.
+            self.map()
+        } else if self.match_types(vec![TokenType::This]) {
+            Expr::This(This {
+                keyword: self.previous(),
+                depth: Cell::new(None),
+                span: self.previous().span(),
+            })
+        } else if self.match_types(vec![TokenType::Super]) {
+            let keyword = self.previous();
+            self.consume(TokenType::Dot, "Expected \".\" after super.");
+            let method = self
+                .consume(TokenType::Identifier, "Expected parent method name.")
+                .unwrap_or_else(|| self.previous());
+            Expr::Super(Super {
+                span: keyword.span().to(method.span()),
+                keyword,
+                method,
+                depth: Cell::new(None),
+            })
         } else {
             self.errors.push(ParseError {
                 token: self.tokens[self.current].clone(),
@@ -236,22 +982,211 @@ impl<'a> Parser<'a> {
             });
             Expr::Literal(Literal {
                 value: LiteralToken::None,
+                span: self.peek().span(),
             })
         }
     }
 
+    // (a, b) => a + b is sugar for gorilla (a, b) { return a + b; }, a braced body is used as is.
+    fn arrow_function(&mut self) -> Expr {
+        let paren = self.advance();
+        let (params, rest) = self.parameters();
+        let arrow = self
+            .consume(TokenType::Arrow, "Expected => after parameters.")
+            .unwrap_or_else(|| self.previous());
+        let body = if self.match_types(vec![TokenType::LeftBrace]) {
+            self.function_body()
+        } else {
+            let value = self.expression();
+            vec![Stmt::Return(Return {
+                span: arrow.span().to(value.span()),
+                keyword: arrow,
+                value: Some(value),
+            })]
+        };
+        let span = self.span_from(paren.span());
+        lambda(&paren, params, rest, body, span)
+    }
+
+    // looks past the matching ) for =>, so (a, b = 1) => ... isn't mistaken for a grouping.
+    fn is_arrow_function(&self) -> bool {
+        let mut depth = 0;
+        for (offset, token) in self.tokens[self.current..].iter().enumerate() {
+            match token.token_type {
+                TokenType::LeftParen => depth += 1,
+                TokenType::RightParen => depth -= 1,
+                TokenType::Eof => return false,
+                _ => {}
+            }
+            if depth == 0 {
+                return self.check_at(offset + 1, TokenType::Arrow);
+            }
+        }
+        false
+    }
+
+    // the scanner hands over "a ${x} b ${y} c" as Interpolation("a ") x Interpolation(" b ") y String(" c").
+    fn interpolation(&mut self) -> Expr {
+        let start = self.previous().span();
+        let mut parts: Vec<Expr> = Vec::new();
+        loop {
+            parts.push(Expr::Literal(Literal {
+                value: self.previous().literal,
+                span: self.previous().span(),
+            }));
+            parts.push(self.expression());
+            if self.match_types(vec![TokenType::Interpolation]) {
+                continue;
+            }
+            if let Some(end) = self.consume(
+                TokenType::String,
+                "Expected } after expression in string interpolation.",
+            ) {
+                parts.push(Expr::Literal(Literal {
+                    span: end.span(),
+                    value: end.literal,
+                }));
+            }
+            break;
+        }
+        Expr::Interpolation(Interpolation {
+            parts,
+            span: self.span_from(start),
+        })
+    }
+
+    // assumes the opening [ has already been consumed, a trailing comma is allowed.
+    fn list(&mut self) -> Expr {
+        let bracket = self.previous();
+        let mut elements: Vec<Expr> = Vec::new();
+        while !self.check(TokenType::RightBracket) && !self.is_at_end() {
+            elements.push(self.expression());
+            if !self.match_types(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expected ] after list elements.");
+        Expr::List(List {
+            span: self.span_from(bracket.span()),
+            bracket,
+            elements,
+        })
+    }
+
+    // assumes the opening { has already been consumed, a trailing comma is allowed.
+    fn map(&mut self) -> Expr {
+        let brace = self.previous();
+        let mut entries: Vec<(Expr, Expr)> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let key = self.expression();
+            self.consume(TokenType::Colon, "Expected : after map key.");
+            let value = self.expression();
+            entries.push((key, value));
+            if !self.match_types(vec![TokenType::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenType::RightBrace, "Expected } after map entries.");
+        Expr::Map(Map {
+            span: self.span_from(brace.span()),
+            brace,
+            entries,
+        })
+    }
+
     // HELPERS:
 
     fn consume(&mut self, token_type: TokenType, msg: &'a str) -> Option<Token> {
         if self.check(token_type) {
-            return Some(self.advance());
+            Some(self.advance())
         } else {
             self.errors.push(ParseError {
                 token: self.peek(),
                 msg,
             });
-            return None;
+            None
+        }
+    }
+
+    // records an error that leaves the parser in a valid state, so there is nothing to synchronise past.
+    fn report(&mut self, token: Token, msg: &'a str) {
+        let recovered = self.synchronised == self.errors.len();
+        self.errors.push(ParseError { token, msg });
+        if recovered {
+            self.synchronised = self.errors.len();
+        }
+    }
+
+    // parses one level further in. Operator chains like 1 + 1 + 1 and a.b().c are parsed in a loop and
+    // don't count, only brackets, blocks, unary operators and statement bodies do. Past MAX_NESTING the
+    // rest of the statement is set aside, so the parser unwinds with an error instead of running out of stack.
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> T) -> T {
+        let depth = self.depth;
+        self.depth += 1;
+        if self.depth > MAX_NESTING && self.nesting_error.is_none() {
+            self.errors.push(ParseError {
+                token: self.peek(),
+                msg: "Too much nesting.",
+            });
+            self.nesting_error = Some(self.errors.len());
+            let end = self.statement_end().max(self.current);
+            let eof = self.tokens[self.tokens.len() - 1].clone();
+            self.held_back = self.tokens.split_off(end);
+            self.tokens.push(eof);
+            self.current = self.tokens.len() - 1;
         }
+        let result = parse(self);
+        self.depth = depth;
+        result
+    }
+
+    // just past the end of the top level statement being parsed, found by matching brackets rather
+    // than parsing so it works from wherever the parser gave up.
+    fn statement_end(&self) -> usize {
+        let braced = matches!(
+            self.tokens[self.statement_start].token_type,
+            TokenType::LeftBrace
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Gorilla
+                | TokenType::Jungle
+                | TokenType::Try
+        );
+        let mut depth: i32 = 0;
+        for index in self.statement_start..self.tokens.len() {
+            let ends = match self.tokens[index].token_type {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => {
+                    depth += 1;
+                    false
+                }
+                TokenType::RightParen | TokenType::RightBracket => {
+                    depth -= 1;
+                    false
+                }
+                TokenType::RightBrace => {
+                    depth -= 1;
+                    braced && depth == 0
+                }
+                TokenType::Semicolon => depth == 0,
+                TokenType::Eof => return index,
+                _ => false,
+            };
+            // an if or try carries on into its else, catch or finally.
+            let continued = matches!(
+                self.tokens.get(index + 1).map(|token| token.token_type),
+                Some(TokenType::Else | TokenType::Catch | TokenType::Finally)
+            );
+            if ends && !continued {
+                return index + 1;
+            }
+        }
+        self.tokens.len() - 1
+    }
+
+    // from start up to the last token consumed.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span())
     }
 
     fn previous(&self) -> Token {
@@ -266,7 +1201,7 @@ impl<'a> Parser<'a> {
             }
         }
 
-        return false;
+        false
     }
 
     fn check(&mut self, t: TokenType) -> bool {
@@ -275,7 +1210,7 @@ impl<'a> Parser<'a> {
         }
         // return a check if type of self.peek() is the same type as type token type passed as argument.
         let current_token = self.peek();
-        &current_token.token_type == &t
+        current_token.token_type == t
     }
 
     fn advance(&mut self) -> Token {
@@ -288,10 +1223,14 @@ impl<'a> Parser<'a> {
 
     fn is_at_end(&self) -> bool {
         let current_token = self.peek();
-        match current_token.token_type {
-            TokenType::Eof => true,
-            _ => false,
-        }
+        matches!(current_token.token_type, TokenType::Eof)
+    }
+
+    // whether the token offset places after the current one has the given type.
+    fn check_at(&self, offset: usize, t: TokenType) -> bool {
+        self.tokens
+            .get(self.current + offset)
+            .is_some_and(|token| token.token_type == t)
     }
 
     fn peek(&self) -> Token {
@@ -302,9 +1241,8 @@ impl<'a> Parser<'a> {
         self.advance();
 
         while !self.is_at_end() {
-            match self.previous().token_type {
-                TokenType::Semicolon => return,
-                _ => {}
+            if self.previous().token_type == TokenType::Semicolon {
+                return;
             }
             match self.peek().token_type {
                 TokenType::Jungle
@@ -314,7 +1252,9 @@ impl<'a> Parser<'a> {
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
+                | TokenType::Return
+                | TokenType::Try
+                | TokenType::Throw => return,
                 _ => {}
             }
             self.advance();
@@ -322,7 +1262,202 @@ impl<'a> Parser<'a> {
     }
 }
 
-// #[cfg(test)]
-// mod test {
-//     use super::Parser;
-// }
+// the token is the gorilla keyword or the arrow's (, it is only kept for its position.
+fn lambda(
+    token: &Token,
+    params: Vec<Parameter>,
+    rest: Option<Token>,
+    body: Vec<Stmt>,
+    span: Span,
+) -> Expr {
+    let name = Token::new(
+        token.token_type,
+        "anonymous".to_string(),
+        LiteralToken::None,
+        token.span(),
+    );
+    Expr::Lambda(Lambda {
+        function: Function {
+            name,
+            params: Rc::new(params),
+            rest,
+            body: Rc::new(body),
+            span,
+        },
+        span,
+    })
+}
+
+// the plain operator a compound assignment applies, so a += 1 runs the same + as a + 1.
+fn compound_operator(equals: &Token) -> Option<Token> {
+    let token_type = match equals.token_type {
+        TokenType::PlusEqual | TokenType::PlusPlus => TokenType::Plus,
+        TokenType::MinusEqual | TokenType::MinusMinus => TokenType::Minus,
+        TokenType::StarEqual => TokenType::Star,
+        TokenType::SlashEqual => TokenType::Slash,
+        TokenType::PercentEqual => TokenType::Percent,
+        _ => return None,
+    };
+    Some(Token::new(
+        token_type,
+        equals.lexeme.clone(),
+        LiteralToken::None,
+        equals.span(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Parser;
+    use crate::ast::expressions::Expr;
+    use crate::ast::statements::Stmt;
+    use crate::errors::parse_error::ParseError;
+    use crate::syntax::scanner::Scanner;
+    use crate::syntax::token::Span;
+
+    fn text(source: &str, span: Span) -> &str {
+        &source[span.offset..span.offset + span.length]
+    }
+
+    fn parse_errors(source: &str) -> Vec<ParseError<'static>> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.scan_tokens();
+        let mut parser = Parser::new(scanner.tokens);
+        parser.parse();
+        parser.errors
+    }
+
+    #[test]
+    fn test_spans() {
+        let source = "let total = price * (1 + rate);\nif (total > 10) print \"ünïcode ${total}\";";
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.scan_tokens();
+        let mut parser = Parser::new(scanner.tokens);
+        let statements = parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+
+        assert_eq!(
+            text(source, statements[0].span()),
+            "let total = price * (1 + rate);"
+        );
+        let Stmt::Let(total) = &statements[0] else {
+            panic!("expected a let statement");
+        };
+        assert_eq!(text(source, total.initialiser.span()), "price * (1 + rate)");
+        let Expr::Binary(product) = &total.initialiser else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(text(source, product.right.span()), "(1 + rate)");
+        assert_eq!(
+            (product.right.span().line, product.right.span().column),
+            (1, 21)
+        );
+
+        let Stmt::If(check) = &statements[1] else {
+            panic!("expected an if statement");
+        };
+        assert_eq!(check.span.line, 2);
+        assert_eq!(text(source, check.condition.span()), "total > 10");
+        assert_eq!(
+            text(source, check.then_branch.span()),
+            "print \"ünïcode ${total}\";"
+        );
+        let Stmt::Print(print) = check.then_branch.as_ref() else {
+            panic!("expected a print statement");
+        };
+        assert_eq!(
+            text(source, print.expression.span()),
+            "\"ünïcode ${total}\""
+        );
+        assert_eq!(print.expression.span().column, 23);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        // this deep the parser needs more than the test thread's stack.
+        let results = std::thread::Builder::new()
+            .stack_size(crate::interpreter::run::STACK_SIZE)
+            .spawn(|| {
+                let parse = |source: String| {
+                    let mut scanner = Scanner::new(source);
+                    scanner.scan_tokens();
+                    let mut parser = Parser::new(scanner.tokens);
+                    let statements = parser.parse().len();
+                    let errors: Vec<String> =
+                        parser.errors.iter().map(|e| e.msg.to_string()).collect();
+                    (statements, errors)
+                };
+                let brackets = |depth| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+                [
+                    parse(format!("print {};", brackets(200))),
+                    parse(format!("print {};", brackets(300))),
+                    parse(format!("print {}true;", "!".repeat(300))),
+                    parse(format!("{}{} print 1;", "{".repeat(300), "}".repeat(300))),
+                    parse(format!("{}print 1;", "if (true) ".repeat(300))),
+                    // parsing carries on with the next statement, whose errors are still reported.
+                    parse(format!("print {}; print 2; print ;", brackets(300))),
+                ]
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        let too_deep = "Too much nesting.".to_string();
+        assert_eq!(results[0], (1, vec![]));
+        for result in [&results[1], &results[2], &results[4]] {
+            assert_eq!(result, &(1, vec![too_deep.clone()]));
+        }
+        // the block is cut short, the print after it is parsed as usual.
+        assert_eq!(results[3], (2, vec![too_deep.clone()]));
+        assert_eq!(
+            results[5],
+            (3, vec![too_deep, "Unexpected token".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_flat_chains() {
+        // operators and calls chained at one level are not nesting, however long they get.
+        for source in [
+            format!("let x = 0{};", " + 1".repeat(300)),
+            format!("let x = a{};", ".b(1)[0]".repeat(300)),
+            format!("let x = true{};", " and x == 1 or false".repeat(300)),
+        ] {
+            let mut scanner = Scanner::new(source);
+            scanner.scan_tokens();
+            let mut parser = Parser::new(scanner.tokens);
+            assert_eq!(parser.parse().len(), 1);
+            assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        }
+    }
+
+    #[test]
+    fn test_break_outside_loop() {
+        let errors = parse_errors("if (true) { break; }");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].msg,
+            "Can only use break and continue inside a loop."
+        );
+    }
+
+    #[test]
+    fn test_for_missing_clause() {
+        let errors = parse_errors("for (let i = 0; i < 3 i = i + 1) print i;");
+        assert_eq!(errors[0].msg, "Expected ; after for loop condition.");
+        assert_eq!(errors[0].token.lexeme, "i");
+    }
+
+    #[test]
+    fn test_invalid_increment_target() {
+        for source in ["1++;", "(x)--;", "++f();", "--x++;"] {
+            let errors = parse_errors(source);
+            assert_eq!(errors[0].msg, "Invalid increment target.", "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_try_without_handler() {
+        let errors = parse_errors("try { print 1; }");
+        assert_eq!(errors[0].msg, "Expected catch or finally after try block.");
+    }
+}
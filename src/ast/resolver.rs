@@ -0,0 +1,450 @@
+use super::expressions::{Depth, Expr};
+use super::statements::{Function, Stmt};
+use super::MAX_NESTING;
+use crate::errors::resolve_error::ResolveError;
+use crate::syntax::token::{Literal, Token, TokenType};
+use std::collections::HashMap;
+
+const TOO_DEEP: &str = "Too much nesting.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionKind {
+    None,
+    Function,
+    Initialiser, // a Jungle's init, which always hands back the instance.
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClassKind {
+    None,
+    Class,
+    Subclass,
+}
+
+// walks the tree once before it is run, recording how far out each local name was declared
+// and rejecting scripts that could never run correctly.
+#[derive(Debug)]
+pub struct Resolver {
+    scopes: Vec<HashMap<String, bool>>, // false while a name is declared but its initialiser has not finished.
+    function: FunctionKind,
+    class: ClassKind,
+    depth: usize, // how many groupings, unary operators, literals and lambdas enclose the current expression.
+    pub errors: Vec<ResolveError>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            function: FunctionKind::None,
+            class: ClassKind::None,
+            depth: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn resolve(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression(e) => self.resolve_expression(&e.expression),
+            Stmt::Print(p) => self.resolve_expression(&p.expression),
+            Stmt::Let(v) => {
+                self.declare(&v.name);
+                self.resolve_expression(&v.initialiser);
+                self.define(&v.name);
+            }
+            Stmt::Import(_) => {}
+            Stmt::Block(block) => {
+                self.begin_scope();
+                self.resolve(&block.statements);
+                self.end_scope();
+            }
+            Stmt::If(stmt) => {
+                self.resolve_expression(&stmt.condition);
+                self.resolve_statement(&stmt.then_branch);
+                if let Some(else_branch) = &stmt.else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            }
+            Stmt::While(stmt) => {
+                self.resolve_expression(&stmt.condition);
+                self.resolve_statement(&stmt.body);
+                if let Some(increment) = &stmt.increment {
+                    self.resolve_expression(increment);
+                }
+            }
+            Stmt::Break(_) | Stmt::Continue(_) => {}
+            Stmt::Function(function) => {
+                // defined before the body so the function can call itself.
+                self.declare(&function.name);
+                self.define(&function.name);
+                self.resolve_function(function, FunctionKind::Function);
+            }
+            Stmt::Return(stmt) => {
+                if self.function == FunctionKind::None {
+                    self.error(&stmt.keyword, "Can't return from top-level code.");
+                }
+                if let Some(value) = &stmt.value {
+                    if self.function == FunctionKind::Initialiser {
+                        self.error(&stmt.keyword, "Can't return a value from an initialiser.");
+                    }
+                    self.resolve_expression(value);
+                }
+            }
+            Stmt::Class(class) => {
+                let enclosing_class = self.class;
+                self.class = ClassKind::Class;
+                self.declare(&class.name);
+                self.define(&class.name);
+
+                if let Some(superclass) = &class.superclass {
+                    if let Expr::Variable(parent) = superclass {
+                        if parent.name.lexeme == class.name.lexeme {
+                            self.error(&parent.name, "A Jungle can't inherit from itself.");
+                        }
+                    }
+                    self.class = ClassKind::Subclass;
+                    self.resolve_expression(superclass);
+                    self.begin_scope();
+                    self.define_name("super");
+                }
+
+                self.begin_scope();
+                self.define_name("this");
+                for method in class.methods.iter() {
+                    let kind = match method.name.lexeme.as_str() {
+                        "init" => FunctionKind::Initialiser,
+                        _ => FunctionKind::Function,
+                    };
+                    self.resolve_function(method, kind);
+                }
+                self.end_scope();
+
+                if class.superclass.is_some() {
+                    self.end_scope();
+                }
+                self.class = enclosing_class;
+            }
+            Stmt::Try(stmt) => {
+                self.begin_scope();
+                self.resolve(&stmt.body);
+                self.end_scope();
+                if let Some(catch_body) = &stmt.catch_body {
+                    // the error name shares a scope with the catch block.
+                    self.begin_scope();
+                    if let Some(name) = &stmt.catch_name {
+                        self.declare(name);
+                        self.define(name);
+                    }
+                    self.resolve(catch_body);
+                    self.end_scope();
+                }
+                if let Some(finally_body) = &stmt.finally_body {
+                    self.begin_scope();
+                    self.resolve(finally_body);
+                    self.end_scope();
+                }
+            }
+            Stmt::Throw(stmt) => self.resolve_expression(&stmt.value),
+        }
+    }
+
+    // the parser already limits nesting, this covers trees that were built some other way. Like the
+    // parser it leaves operator and call chains uncounted, and every level counted here is one there.
+    fn resolve_expression(&mut self, expr: &Expr) {
+        let nests = matches!(
+            expr,
+            Expr::Grouping(_) | Expr::Unary(_) | Expr::List(_) | Expr::Map(_) | Expr::Lambda(_)
+        );
+        if nests && self.depth == MAX_NESTING {
+            if !self.errors.iter().any(|e| e.msg == TOO_DEEP) {
+                let token = Token::new(TokenType::Eof, String::new(), Literal::None, expr.span());
+                self.error(&token, TOO_DEEP);
+            }
+            return;
+        }
+        self.depth += usize::from(nests);
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Grouping(grouping) => self.resolve_expression(&grouping.expression),
+            Expr::Unary(unary) => self.resolve_expression(&unary.right),
+            Expr::Binary(binary) => {
+                self.resolve_expression(&binary.left);
+                self.resolve_expression(&binary.right);
+            }
+            Expr::Logical(logical) => {
+                self.resolve_expression(&logical.left);
+                self.resolve_expression(&logical.right);
+            }
+            Expr::Conditional(conditional) => {
+                self.resolve_expression(&conditional.condition);
+                self.resolve_expression(&conditional.then_branch);
+                self.resolve_expression(&conditional.else_branch);
+            }
+            Expr::Coalesce(coalesce) => {
+                self.resolve_expression(&coalesce.left);
+                self.resolve_expression(&coalesce.right);
+            }
+            Expr::Variable(var) => {
+                if let Some(scope) = self.scopes.last() {
+                    if scope.get(&var.name.lexeme) == Some(&false) {
+                        self.error(
+                            &var.name,
+                            "Can't read local variable in its own initialiser.",
+                        );
+                    }
+                }
+                self.resolve_local(&var.name, &var.depth);
+            }
+            Expr::Assign(assign) => {
+                self.resolve_expression(&assign.value);
+                self.resolve_local(&assign.name, &assign.depth);
+            }
+            Expr::Call(call) => {
+                self.resolve_expression(&call.callee);
+                for argument in call.arguments.iter() {
+                    self.resolve_expression(argument);
+                }
+                for (_, argument) in call.named.iter() {
+                    self.resolve_expression(argument);
+                }
+            }
+            Expr::Get(get) => self.resolve_expression(&get.object),
+            Expr::Set(set) => {
+                self.resolve_expression(&set.value);
+                self.resolve_expression(&set.object);
+            }
+            Expr::This(this) => {
+                if self.class == ClassKind::None {
+                    self.error(&this.keyword, "Can't use this outside of a Jungle.");
+                } else {
+                    self.resolve_local(&this.keyword, &this.depth);
+                }
+            }
+            Expr::Super(sup) => {
+                match self.class {
+                    ClassKind::None => {
+                        self.error(&sup.keyword, "Can't use super outside of a Jungle.")
+                    }
+                    ClassKind::Class => self.error(
+                        &sup.keyword,
+                        "Can't use super in a Jungle with no parent Jungle.",
+                    ),
+                    ClassKind::Subclass => {}
+                }
+                self.resolve_local(&sup.keyword, &sup.depth);
+            }
+            Expr::List(list) => {
+                for element in list.elements.iter() {
+                    self.resolve_expression(element);
+                }
+            }
+            Expr::Index(index) => {
+                self.resolve_expression(&index.object);
+                self.resolve_expression(&index.index);
+            }
+            Expr::IndexSet(index) => {
+                self.resolve_expression(&index.value);
+                self.resolve_expression(&index.object);
+                self.resolve_expression(&index.index);
+            }
+            Expr::Interpolation(interpolation) => {
+                for part in interpolation.parts.iter() {
+                    self.resolve_expression(part);
+                }
+            }
+            Expr::Lambda(lambda) => self.resolve_function(&lambda.function, FunctionKind::Function),
+            Expr::Map(map) => {
+                for (key, value) in map.entries.iter() {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+        }
+        self.depth -= usize::from(nests);
+    }
+
+    fn resolve_function(&mut self, function: &Function, kind: FunctionKind) {
+        let enclosing_function = self.function;
+        self.function = kind;
+        self.begin_scope();
+        // a default can use the parameters declared before it.
+        for param in function.params.iter() {
+            if let Some(default) = &param.default {
+                self.resolve_expression(default);
+            }
+            self.declare(&param.name);
+            self.define(&param.name);
+        }
+        if let Some(rest) = &function.rest {
+            self.declare(rest);
+            self.define(rest);
+        }
+        self.resolve(&function.body);
+        self.end_scope();
+        self.function = enclosing_function;
+    }
+
+    // names not found in any local scope are left unresolved and looked up as globals at runtime.
+    fn resolve_local(&mut self, name: &Token, depth: &Depth) {
+        for (distance, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                depth.set(Some(distance));
+                return;
+            }
+        }
+    }
+
+    // HELPERS:
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    // globals may be redeclared, locals may not.
+    fn declare(&mut self, name: &Token) {
+        let duplicate = match self.scopes.last_mut() {
+            Some(scope) => scope.insert(name.lexeme.clone(), false).is_some(),
+            None => false,
+        };
+        if duplicate {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        self.define_name(&name.lexeme);
+    }
+
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, msg: &'static str) {
+        self.errors.push(ResolveError {
+            token: token.clone(),
+            msg,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Resolver;
+    use crate::ast::expressions::{Expr, Grouping, Literal};
+    use crate::ast::parser::Parser;
+    use crate::ast::statements::{Expression, Stmt};
+    use crate::syntax::scanner::Scanner;
+    use crate::syntax::token::{Literal as LiteralToken, Span};
+
+    fn resolve_errors(source: &str) -> Vec<&'static str> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.scan_tokens();
+        let mut parser = Parser::new(scanner.tokens);
+        let statements = parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements);
+        resolver.errors.iter().map(|e| e.msg).collect()
+    }
+
+    #[test]
+    fn test_valid_script() {
+        let errors = resolve_errors(
+            r#"
+            let a = 1;
+            let a = a + 1;
+            gorilla outer() {
+                let b = a;
+                gorilla inner() { return b; }
+                return inner;
+            }
+            Jungle Base { init() { this.x = 1; } }
+            Jungle Child < Base { init() { super.init(); } }
+            "#,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_semantic_errors() {
+        assert_eq!(
+            resolve_errors("{ let a = 1; { let a = a; } }"),
+            vec!["Can't read local variable in its own initialiser."]
+        );
+        assert_eq!(
+            resolve_errors("return 1;"),
+            vec!["Can't return from top-level code."]
+        );
+        assert_eq!(
+            resolve_errors("gorilla f() { return this; }"),
+            vec!["Can't use this outside of a Jungle."]
+        );
+        assert_eq!(
+            resolve_errors("gorilla f(a, a) {} { let b = 1; let b = 2; }"),
+            vec![
+                "Already a variable with this name in this scope.",
+                "Already a variable with this name in this scope."
+            ]
+        );
+        assert_eq!(
+            resolve_errors("Jungle A < A {}"),
+            vec!["A Jungle can't inherit from itself."]
+        );
+        assert_eq!(
+            resolve_errors("Jungle A { f() { return super.f(); } }"),
+            vec!["Can't use super in a Jungle with no parent Jungle."]
+        );
+        assert_eq!(
+            resolve_errors("Jungle A { init() { return 1; } }"),
+            vec!["Can't return a value from an initialiser."]
+        );
+        // a bare return is fine, and a gorilla inside init may return what it likes.
+        assert!(resolve_errors(
+            "Jungle A { init() { gorilla f() { return 1; } if (f()) return; } }"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        // the parser never builds a tree this deep, so it is put together by hand.
+        let mut expression = Expr::Literal(Literal {
+            value: LiteralToken::None,
+            span: Span::default(),
+        });
+        for _ in 0..300 {
+            expression = Expr::Grouping(Grouping {
+                expression: Box::new(expression),
+                span: Span::default(),
+            });
+        }
+        let mut resolver = Resolver::new();
+        resolver.resolve(&[Stmt::Expression(Expression {
+            expression,
+            span: Span::default(),
+        })]);
+        let errors: Vec<&str> = resolver.errors.iter().map(|e| e.msg).collect();
+        assert_eq!(errors, vec!["Too much nesting."]);
+
+        // a chain of operators is one level however long it is.
+        assert!(resolve_errors(&format!("let x = 0{};", " + 1".repeat(300))).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use super::expressions::Expr;
 use crate::{define_ast, syntax::token::Token};
+use std::rc::Rc;
 
 define_ast! {
 Stmt,
@@ -14,6 +15,60 @@ Let {
  initialiser: Expr
 },
 Import {
+ keyword: Token,
  file_name: String
+},
+Block {
+ statements: Vec<Stmt>
+},
+If {
+ condition: Expr,
+ then_branch: Box<Stmt>,
+ else_branch: Option<Box<Stmt>>
+},
+While {
+ condition: Expr,
+ body: Box<Stmt>,
+ // a for loop's increment, kept out of the body so a continue in the body can't skip it.
+ increment: Option<Expr>
+},
+Break {
+ keyword: Token
+},
+Continue {
+ keyword: Token
+},
+Function {
+ name: Token,
+ params: Rc<Vec<Parameter>>,
+ rest: Option<Token>, // the ...name parameter that collects extra positional arguments into a list.
+ body: Rc<Vec<Stmt>>
+},
+Return {
+ keyword: Token,
+ value: Option<Expr> // None for a bare return;
+},
+Class {
+ name: Token,
+ superclass: Option<Expr>,
+ methods: Vec<Function>
+},
+Try {
+ keyword: Token,
+ body: Vec<Stmt>,
+ catch_name: Option<Token>, // catch without a name still catches, it just can't look at the error.
+ catch_body: Option<Vec<Stmt>>,
+ finally_body: Option<Vec<Stmt>>
+},
+Throw {
+ keyword: Token,
+ value: Expr
+}
 }
+
+// a declared parameter, the default is evaluated by each call that leaves it out, after the parameters before it are bound.
+#[derive(Debug)]
+pub struct Parameter {
+    pub name: Token,
+    pub default: Option<Expr>,
 }
@@ -1,4 +1,5 @@
 pub mod parse_error;
+pub mod resolve_error;
 pub mod runtime_error;
 pub mod syntax_error;
 
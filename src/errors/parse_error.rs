@@ -1,5 +1,5 @@
 use super::Error;
-use crate::syntax::token::Token;
+use crate::syntax::token::{Span, Token};
 
 #[derive(Debug)]
 pub struct ParseError<'a> {
@@ -7,6 +7,13 @@ pub struct ParseError<'a> {
     pub msg: &'a str,
 }
 
+impl<'a> ParseError<'a> {
+    // the token the parser was looking at when it gave up.
+    pub fn span(&self) -> Span {
+        self.token.span()
+    }
+}
+
 impl<'a> Error for ParseError<'a> {
     fn report(&self) {
         println!("Parse Error on line {}: {}", self.token.line, self.msg);
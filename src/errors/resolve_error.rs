@@ -0,0 +1,14 @@
+use super::Error;
+use crate::syntax::token::Token;
+
+#[derive(Debug)]
+pub struct ResolveError {
+    pub token: Token,
+    pub msg: &'static str,
+}
+
+impl Error for ResolveError {
+    fn report(&self) {
+        println!("Resolve Error on line {}: {}", self.token.line, self.msg);
+    }
+}
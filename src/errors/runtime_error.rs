@@ -1,12 +1,79 @@
 use super::Error;
+use crate::interpreter::value::Value;
+use crate::syntax::token::Span;
+use std::fmt;
+
+// runaway recursion would otherwise print a line for every call.
+const MAX_TRACE: usize = 20;
+
+// what went wrong, a caught error exposes this as its kind field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Type,
+    Name,
+    Index,
+    Property,
+    Argument,
+    DivisionByZero,
+    Overflow,
+    Import,
+    Recursion,
+    Thrown, // anything raised with throw that doesn't say otherwise.
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ErrorKind::Type => "TypeError",
+            ErrorKind::Name => "NameError",
+            ErrorKind::Index => "IndexError",
+            ErrorKind::Property => "PropertyError",
+            ErrorKind::Argument => "ArgumentError",
+            ErrorKind::DivisionByZero => "DivisionByZeroError",
+            ErrorKind::Overflow => "OverflowError",
+            ErrorKind::Import => "ImportError",
+            ErrorKind::Recursion => "RecursionError",
+            ErrorKind::Thrown => "Error",
+        };
+        write!(f, "{}", name)
+    }
+}
 
 #[derive(Debug)]
 pub struct RuntimeError {
     pub msg: String,
+    pub kind: ErrorKind,
+    pub span: Span,
+    pub thrown: Option<Value>, // the value given to throw, a catch hands it back unchanged.
+    pub trace: Vec<String>,    // the calls the error unwound through, innermost first.
+}
+
+impl RuntimeError {
+    pub fn new(kind: ErrorKind, msg: String, span: Span) -> Self {
+        Self {
+            msg,
+            kind,
+            span,
+            thrown: None,
+            trace: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on line {}", self.msg, self.span.line)
+    }
 }
 
 impl Error for RuntimeError {
     fn report(&self) {
-        println!("Runtime error: {} ", self.msg);
+        println!("Runtime error: {}: {}", self.kind, self);
+        for frame in self.trace.iter().take(MAX_TRACE) {
+            println!("    in {}", frame);
+        }
+        if self.trace.len() > MAX_TRACE {
+            println!("    ... {} more calls", self.trace.len() - MAX_TRACE);
+        }
     }
 }
@@ -1,37 +1,107 @@
 use super::Error;
+use crate::syntax::token::Span;
 
 #[derive(Debug, Clone)]
 pub enum SyntaxError {
     UnexpectedToken(UnexpectedToken),
     UnterminatedString(UnterminatedString),
+    UnterminatedInterpolation(UnterminatedInterpolation),
+    InvalidEscape(InvalidEscape),
+    MalformedNumber(MalformedNumber),
 }
 #[derive(Debug, Clone)]
 pub struct UnexpectedToken {
-    line: usize,
+    span: Span,
     message: String,
-    token: char,
 }
 #[derive(Debug, Clone)]
 pub struct UnterminatedString {
-    line: usize,
+    span: Span, // the opening quote.
     message: String,
 }
+#[derive(Debug, Clone)]
+pub struct UnterminatedInterpolation {
+    span: Span, // the opening quote of the string holding the ${.
+    message: String,
+}
+#[derive(Debug, Clone)]
+pub struct InvalidEscape {
+    span: Span,
+    message: String,
+}
+#[derive(Debug, Clone)]
+pub struct MalformedNumber {
+    span: Span,
+    message: String,
+}
+
+impl SyntaxError {
+    // the source the scanner rejected, for a string the opening quote.
+    pub fn span(&self) -> Span {
+        match self {
+            SyntaxError::UnexpectedToken(e) => e.span,
+            SyntaxError::UnterminatedString(e) => e.span,
+            SyntaxError::UnterminatedInterpolation(e) => e.span,
+            SyntaxError::InvalidEscape(e) => e.span,
+            SyntaxError::MalformedNumber(e) => e.span,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            SyntaxError::UnexpectedToken(e) => &e.message,
+            SyntaxError::UnterminatedString(e) => &e.message,
+            SyntaxError::UnterminatedInterpolation(e) => &e.message,
+            SyntaxError::InvalidEscape(e) => &e.message,
+            SyntaxError::MalformedNumber(e) => &e.message,
+        }
+    }
+}
 
 impl UnexpectedToken {
-    pub fn new(token: char, line: usize) -> Self {
+    pub fn new(token: char, span: Span) -> Self {
         Self {
-            token,
-            line,
-            message: format!("Unexpected token {} on line {}", token, line),
+            span,
+            message: format!("Unexpected token {} on line {}", token, span.line),
         }
     }
 }
 
 impl UnterminatedString {
-    pub fn new(line: usize) -> Self {
+    pub fn new(span: Span) -> Self {
+        Self {
+            span,
+            message: format!("Unterminated string on line {}", span.line),
+        }
+    }
+}
+
+impl UnterminatedInterpolation {
+    pub fn new(span: Span) -> Self {
+        Self {
+            span,
+            message: format!("Unterminated string interpolation on line {}", span.line),
+        }
+    }
+}
+
+impl InvalidEscape {
+    pub fn new(sequence: String, span: Span) -> Self {
+        Self {
+            span,
+            message: format!(
+                "Invalid escape sequence {} on line {} column {}",
+                sequence, span.line, span.column
+            ),
+        }
+    }
+}
+
+impl MalformedNumber {
+    pub fn new(lexeme: String, span: Span) -> Self {
         Self {
-            line,
-            message: format!("Unterminated string on line {}", line),
+            span,
+            message: format!("Malformed number {} on line {}", lexeme, span.line),
         }
     }
 }
@@ -48,11 +118,32 @@ impl Error for UnterminatedString {
     }
 }
 
+impl Error for UnterminatedInterpolation {
+    fn report(&self) {
+        println!("Syntax Error: {}", self.message);
+    }
+}
+
+impl Error for InvalidEscape {
+    fn report(&self) {
+        println!("Syntax Error: {}", self.message);
+    }
+}
+
+impl Error for MalformedNumber {
+    fn report(&self) {
+        println!("Syntax Error: {}", self.message);
+    }
+}
+
 impl Error for SyntaxError {
     fn report(&self) {
         match self {
             SyntaxError::UnexpectedToken(e) => e.report(),
             SyntaxError::UnterminatedString(e) => e.report(),
+            SyntaxError::UnterminatedInterpolation(e) => e.report(),
+            SyntaxError::InvalidEscape(e) => e.report(),
+            SyntaxError::MalformedNumber(e) => e.report(),
         }
     }
 }
@@ -0,0 +1,486 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// how a division result is rounded once it has as many digits as the context allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    HalfEven, // ties go to the even neighbour, 2.5 -> 2 and 3.5 -> 4.
+    HalfUp,   // ties go away from zero, 2.5 -> 3 and -2.5 -> -3.
+    Down,     // towards zero.
+    Up,       // away from zero.
+    Floor,    // towards negative infinity.
+    Ceiling,  // towards positive infinity.
+}
+
+impl Rounding {
+    // the names the command line takes, like half-even.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "half-even" => Some(Rounding::HalfEven),
+            "half-up" => Some(Rounding::HalfUp),
+            "down" => Some(Rounding::Down),
+            "up" => Some(Rounding::Up),
+            "floor" => Some(Rounding::Floor),
+            "ceiling" => Some(Rounding::Ceiling),
+            _ => None,
+        }
+    }
+}
+
+// division is the only operation that can't always be exact, so it is the only one that needs a context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalContext {
+    pub scale: u32, // digits kept after the decimal point.
+    pub rounding: Rounding,
+}
+
+impl Default for DecimalContext {
+    fn default() -> Self {
+        Self {
+            scale: 20,
+            rounding: Rounding::HalfEven,
+        }
+    }
+}
+
+// literals and powers can't ask for more digits than this, so 1e999999999d and 9999999999d ** 5000
+// fail straight away instead of exhausting memory or multiplying for minutes.
+pub(crate) const MAX_DIGITS: usize = 10_000;
+
+// an exact base 10 number, digits * 10^-scale. The scale is kept, so 1.50d prints as 1.50.
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    negative: bool,
+    digits: Vec<u8>, // least significant first, without leading zeros, so zero has no digits.
+    scale: u32,
+}
+
+impl Decimal {
+    fn new(negative: bool, digits: Vec<u8>, scale: u32) -> Self {
+        let digits = trim(digits);
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale,
+        }
+    }
+
+    pub fn from_i64(n: i64) -> Self {
+        let mut digits = Vec::new();
+        let mut rest = n.unsigned_abs();
+        while rest > 0 {
+            digits.push((rest % 10) as u8);
+            rest /= 10;
+        }
+        Self::new(n < 0, digits, 0)
+    }
+
+    // the exact value of a float, so 0.1 becomes 0.1000000000000000055511151231257827021181583404541015625.
+    pub fn from_f64(n: f64) -> Option<Self> {
+        if !n.is_finite() {
+            return None;
+        }
+        let bits = n.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exponent) = match exponent {
+            0 => (fraction, -1074),
+            _ => (fraction | (1 << 52), exponent - 1075),
+        };
+        let mut digits = Decimal::from_i64(mantissa as i64).digits;
+        if exponent >= 0 {
+            for _ in 0..exponent {
+                digits = mul_small(&digits, 2);
+            }
+            Some(Self::new(n < 0.0, digits, 0))
+        } else {
+            // m / 2^k is m * 5^k / 10^k.
+            for _ in 0..-exponent {
+                digits = mul_small(&digits, 5);
+            }
+            Some(Self::new(n < 0.0, digits, -exponent as u32).reduce())
+        }
+    }
+
+    // parses plain decimal text like 19.99, -0.5 or 1.5e-3, underscores have to be removed already.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(i) => (&text[..i], text[(i + 1)..].parse::<i64>().ok()?),
+            None => (text, 0),
+        };
+        if exponent.unsigned_abs() > MAX_DIGITS as u64 {
+            return None;
+        }
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty()
+            || !(integer.chars().chain(fraction.chars())).all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits: Vec<u8> = integer
+            .bytes()
+            .chain(fraction.bytes())
+            .rev()
+            .map(|b| b - b'0')
+            .collect();
+        let scale = fraction.len() as i64 - exponent;
+        if scale >= 0 {
+            Some(Self::new(negative, digits, scale as u32))
+        } else {
+            Some(Self::new(negative, shift(&digits, -scale as u32), 0))
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    // Some when the value is a whole number that fits in an Int.
+    pub fn to_i64(&self) -> Option<i64> {
+        let reduced = self.reduce();
+        if reduced.scale > 0 || reduced.digits.len() > 19 {
+            return None;
+        }
+        let mut n: i64 = 0;
+        for &digit in reduced.digits.iter().rev() {
+            n = n.checked_mul(10)?.checked_add(digit as i64)?;
+        }
+        match reduced.negative {
+            true => n.checked_neg(),
+            false => Some(n),
+        }
+    }
+
+    // the nearest float, rust's parser rounds correctly.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    // drops trailing zeros after the decimal point, so equal values have the same digits.
+    pub fn reduce(&self) -> Self {
+        let zeros = self
+            .digits
+            .iter()
+            .take(self.scale as usize)
+            .take_while(|&&d| d == 0)
+            .count();
+        let zeros = match self.digits.is_empty() {
+            true => self.scale as usize,
+            false => zeros,
+        };
+        Self::new(
+            self.negative,
+            self.digits[zeros.min(self.digits.len())..].to_vec(),
+            self.scale - zeros as u32,
+        )
+    }
+
+    pub fn neg(&self) -> Self {
+        Self::new(!self.negative, self.digits.clone(), self.scale)
+    }
+
+    pub fn add(&self, rhs: &Self) -> Self {
+        let (a, b, scale) = align(self, rhs);
+        if self.negative == rhs.negative {
+            return Self::new(self.negative, add_digits(&a, &b), scale);
+        }
+        match compare_digits(&a, &b) {
+            Ordering::Less => Self::new(rhs.negative, sub_digits(&b, &a), scale),
+            _ => Self::new(self.negative, sub_digits(&a, &b), scale),
+        }
+    }
+
+    pub fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.neg())
+    }
+
+    pub fn mul(&self, rhs: &Self) -> Self {
+        Self::new(
+            self.negative != rhs.negative,
+            mul_digits(&self.digits, &rhs.digits),
+            self.scale + rhs.scale,
+        )
+    }
+
+    // None when dividing by zero. The quotient is rounded to the context's scale, then trailing zeros are dropped.
+    pub fn div(&self, rhs: &Self, context: DecimalContext) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        // (a / 10^sa) / (b / 10^sb) at scale s is a * 10^(sb + s) / (b * 10^sa).
+        let numerator = shift(&self.digits, rhs.scale + context.scale);
+        let denominator = shift(&rhs.digits, self.scale);
+        let (quotient, remainder) = div_rem(&numerator, &denominator);
+        let negative = self.negative != rhs.negative;
+        let half = compare_digits(&mul_small(&remainder, 2), &denominator);
+        let odd = quotient.first().is_some_and(|d| d % 2 == 1);
+        let round_away = !remainder.is_empty()
+            && match context.rounding {
+                Rounding::HalfEven => half == Ordering::Greater || (half == Ordering::Equal && odd),
+                Rounding::HalfUp => half != Ordering::Less,
+                Rounding::Down => false,
+                Rounding::Up => true,
+                Rounding::Floor => negative,
+                Rounding::Ceiling => !negative,
+            };
+        let quotient = match round_away {
+            true => add_digits(&quotient, &[1]),
+            false => quotient,
+        };
+        Some(Self::new(negative, quotient, context.scale).reduce())
+    }
+
+    // None when the result could have more than MAX_DIGITS digits, which is checked before multiplying.
+    pub fn powi(&self, exponent: u32) -> Option<Self> {
+        if self.digits.len().saturating_mul(exponent as usize) > MAX_DIGITS {
+            return None;
+        }
+        let mut result = Decimal::from_i64(1);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result.mul(&base);
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        Some(result)
+    }
+
+    // the quotient truncated towards zero and the remainder, which takes the sign of self. rhs must not be zero.
+    pub fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        let (a, b, scale) = align(self, rhs);
+        let (quotient, remainder) = div_rem(&a, &b);
+        (
+            Self::new(self.negative != rhs.negative, quotient, 0),
+            Self::new(self.negative, remainder, scale),
+        )
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = align(self, other);
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_digits(&a, &b),
+            (true, true) => compare_digits(&b, &a),
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = self.scale as usize;
+        // pad so there is always at least one digit before the decimal point.
+        let mut digits = self.digits.clone();
+        digits.resize(digits.len().max(scale + 1), 0);
+        let text: String = digits.iter().rev().map(|d| (b'0' + d) as char).collect();
+        let (integer, fraction) = text.split_at(text.len() - scale);
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match fraction.is_empty() {
+            true => write!(f, "{}", integer),
+            false => write!(f, "{}.{}", integer, fraction),
+        }
+    }
+}
+
+// DIGIT HELPERS, all digit vectors are least significant first:
+
+fn trim(mut digits: Vec<u8>) -> Vec<u8> {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    digits
+}
+
+// multiplies by 10^places.
+fn shift(digits: &[u8], places: u32) -> Vec<u8> {
+    if digits.is_empty() {
+        return Vec::new();
+    }
+    let mut shifted = vec![0; places as usize];
+    shifted.extend_from_slice(digits);
+    shifted
+}
+
+// both digit vectors at the larger of the two scales.
+fn align(a: &Decimal, b: &Decimal) -> (Vec<u8>, Vec<u8>, u32) {
+    let scale = a.scale.max(b.scale);
+    (
+        shift(&a.digits, scale - a.scale),
+        shift(&b.digits, scale - b.scale),
+        scale,
+    )
+}
+
+fn compare_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let digit = a.get(i).unwrap_or(&0) + b.get(i).unwrap_or(&0) + carry;
+        sum.push(digit % 10);
+        carry = digit / 10;
+    }
+    if carry > 0 {
+        sum.push(carry);
+    }
+    sum
+}
+
+// a has to be at least b.
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &digit) in a.iter().enumerate() {
+        let subtrahend = b.get(i).unwrap_or(&0) + borrow;
+        if digit >= subtrahend {
+            difference.push(digit - subtrahend);
+            borrow = 0;
+        } else {
+            difference.push(digit + 10 - subtrahend);
+            borrow = 1;
+        }
+    }
+    trim(difference)
+}
+
+fn mul_small(a: &[u8], n: u8) -> Vec<u8> {
+    mul_digits(a, &[n])
+}
+
+fn mul_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] += x as u32 * y as u32;
+        }
+        // carry after each row so no column can overflow.
+        let mut carry = 0;
+        for digit in product.iter_mut().skip(i) {
+            *digit += carry;
+            carry = *digit / 10;
+            *digit %= 10;
+        }
+    }
+    trim(product.into_iter().map(|d| d as u8).collect())
+}
+
+// schoolbook long division, one quotient digit at a time from the most significant end.
+fn div_rem(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder: Vec<u8> = Vec::new();
+    for i in (0..a.len()).rev() {
+        remainder = trim(shift(&remainder, 1));
+        if a[i] > 0 {
+            remainder = add_digits(&remainder, &[a[i]]);
+        }
+        while compare_digits(&remainder, b) != Ordering::Less {
+            remainder = sub_digits(&remainder, b);
+            quotient[i] += 1;
+        }
+    }
+    (trim(quotient), remainder)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Decimal, DecimalContext, Rounding};
+
+    fn decimal(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let amount = decimal("100");
+        let discount = decimal("0.2");
+        assert_eq!(amount.sub(&amount.mul(&discount)).to_string(), "80.0");
+        assert_eq!(decimal("0.1").add(&decimal("0.2")).to_string(), "0.3");
+        assert_eq!(decimal("19.99").mul(&decimal("3")).to_string(), "59.97");
+        assert_eq!(decimal("1.5").sub(&decimal("2.25")).to_string(), "-0.75");
+        assert_eq!(decimal("1.5e-3").to_string(), "0.0015");
+        assert_eq!(decimal("2.5e2").to_string(), "250");
+        assert!(decimal("1.50") == decimal("1.5"));
+        assert!(decimal("-2") < decimal("0.001"));
+        assert_eq!(decimal("-12.50").to_i64(), None);
+        assert_eq!(decimal("-12.00").to_i64(), Some(-12));
+        assert_eq!(Decimal::from_f64(0.25).unwrap().to_string(), "0.25");
+        assert_eq!(decimal("-1.5").powi(3).unwrap().to_string(), "-3.375");
+        assert!(decimal("2").powi(20_000).is_none());
+        assert!(decimal("1.5").powi(5000).is_some());
+        let (quotient, remainder) = decimal("-7.5").div_rem(&decimal("2"));
+        assert_eq!(
+            (quotient.to_string(), remainder.to_string()),
+            ("-3".to_string(), "-1.5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_huge_power() {
+        // the size of the result is checked before any multiplying, so this fails straight away.
+        let start = std::time::Instant::now();
+        assert!(decimal("9999999999").powi(5000).is_none());
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_division() {
+        let context = |scale, rounding| DecimalContext { scale, rounding };
+        let third = decimal("1").div(&decimal("3"), DecimalContext::default());
+        assert_eq!(third.unwrap().to_string(), "0.33333333333333333333");
+        assert_eq!(
+            decimal("10")
+                .div(&decimal("4"), DecimalContext::default())
+                .unwrap()
+                .to_string(),
+            "2.5"
+        );
+        assert!(decimal("1")
+            .div(&decimal("0.00"), DecimalContext::default())
+            .is_none());
+        for (rounding, up, down) in [
+            (Rounding::HalfEven, "0.2", "-0.2"),
+            (Rounding::HalfUp, "0.3", "-0.3"),
+            (Rounding::Down, "0.2", "-0.2"),
+            (Rounding::Up, "0.3", "-0.3"),
+            (Rounding::Floor, "0.2", "-0.3"),
+            (Rounding::Ceiling, "0.3", "-0.2"),
+        ] {
+            let half = decimal("0.5").div(&decimal("2"), context(1, rounding));
+            let negative = decimal("-0.5").div(&decimal("2"), context(1, rounding));
+            assert_eq!(half.unwrap().to_string(), up, "{:?}", rounding);
+            assert_eq!(negative.unwrap().to_string(), down, "{:?}", rounding);
+        }
+    }
+}
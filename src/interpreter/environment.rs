@@ -1,22 +1,92 @@
 use super::Value;
+use crate::errors::runtime_error::{ErrorKind, RuntimeError};
 use crate::syntax::token::Token;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
+// scopes are shared, a closure keeps its defining scope alive after the block or call that created it has ended.
+#[derive(Default)]
 pub struct Environment {
     pub values: HashMap<String, Value>,
+    pub enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         let values = HashMap::new();
-        Self { values }
+        Self {
+            values,
+            enclosing: None,
+        }
     }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
     pub fn define(&mut self, name: String, value: Value) {
         self.values.insert(name, value);
     }
 
     // if not present just return runtime error in interpreter.
-    pub fn get_value(&self, token: Token) -> Option<Value> {
-        self.values.get(&token.lexeme).cloned()
+    pub fn get_value(&self, token: &Token) -> Option<Value> {
+        self.lookup(&token.lexeme)
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow().lookup(name),
+                None => None,
+            },
+        }
+    }
+
+    // updates the innermost existing binding, unlike define this never creates a new one.
+    pub fn assign(&mut self, token: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&token.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(token, value),
+            None => Err(RuntimeError::new(
+                ErrorKind::Name,
+                format!("Undefined variable '{}'", token.lexeme),
+                token.span(),
+            )),
+        }
+    }
+
+    // the scope `distance` steps out from this one, as recorded by the resolver.
+    pub fn ancestor(env: &Rc<RefCell<Environment>>, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut scope = Rc::clone(env);
+        for _ in 0..distance {
+            let enclosing = match &scope.borrow().enclosing {
+                Some(enclosing) => Rc::clone(enclosing),
+                None => break,
+            };
+            scope = enclosing;
+        }
+        scope
+    }
+
+    pub fn get_at(env: &Rc<RefCell<Environment>>, distance: usize, name: &str) -> Option<Value> {
+        Environment::ancestor(env, distance)
+            .borrow()
+            .values
+            .get(name)
+            .cloned()
+    }
+
+    pub fn assign_at(env: &Rc<RefCell<Environment>>, distance: usize, token: &Token, value: Value) {
+        Environment::ancestor(env, distance)
+            .borrow_mut()
+            .define(token.lexeme.clone(), value);
     }
 }
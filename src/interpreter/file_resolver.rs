@@ -1,14 +1,34 @@
+use crate::ast::parser::Parser;
+use crate::ast::resolver::Resolver;
 use crate::ast::statements::Stmt;
-use crate::errors::runtime_error::RuntimeError;
-use crate::errors::syntax_error::{SyntaxError, UnexpectedToken};
+use crate::errors::parse_error::ParseError;
+use crate::errors::resolve_error::ResolveError;
+use crate::errors::syntax_error::SyntaxError;
 use crate::syntax::scanner::Scanner;
-use crate::{ast::parser::Parser, errors::Error};
+use std::fmt;
 use std::fs;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum ImportError {
     Syntax(SyntaxError),
-    FileNotFound(RuntimeError),
+    Parse(ParseError<'static>),
+    Resolve(ResolveError),
+    FileNotFound(std::io::Error),
+    Cycle, // the file is already part way through being imported.
+}
+
+// why the file could not be imported, it ends up in the message of the import's runtime error.
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::Syntax(e) => write!(f, "{}", e.message()),
+            ImportError::Parse(e) => write!(f, "{} on line {}", e.msg, e.token.line),
+            ImportError::Resolve(e) => write!(f, "{} on line {}", e.msg, e.token.line),
+            ImportError::FileNotFound(e) => write!(f, "{}", e),
+            ImportError::Cycle => write!(f, "it is already being imported"),
+        }
+    }
 }
 
 pub fn create_statement_stream(file_name: &str) -> Result<Vec<Stmt>, ImportError> {
@@ -17,34 +37,39 @@ pub fn create_statement_stream(file_name: &str) -> Result<Vec<Stmt>, ImportError
         Ok(content) => {
             let mut scanner = Scanner::new(content);
             scanner.scan_tokens();
-            if scanner.errors.len() > 0 {
-                return Err(ImportError::Syntax(SyntaxError::from(
-                    scanner.errors[0].clone(),
-                )));
+            if !scanner.errors.is_empty() {
+                return Err(ImportError::Syntax(scanner.errors[0].clone()));
             }
             let mut parser = Parser::new(scanner.tokens);
             let stmts = parser.parse();
+            if !parser.errors.is_empty() {
+                return Err(ImportError::Parse(parser.errors.remove(0)));
+            }
+            let mut resolver = Resolver::new();
+            resolver.resolve(&stmts);
+            if !resolver.errors.is_empty() {
+                return Err(ImportError::Resolve(resolver.errors.remove(0)));
+            }
             Ok(stmts)
         }
         Err(e) => Err(ImportError::FileNotFound(e)),
     }
 }
 
-fn find_file(file_name: String) -> Result<String, RuntimeError> {
-    let file = fs::read_to_string(clean_file_name(&file_name));
-    match file {
-        Ok(content) => Ok(content),
-        Err(_) => Err(RuntimeError {
-            msg: "Could not find import".to_string(),
-        }),
-    }
+// the same file reached by two different names, like a.grl and ./a.grl, gives the same path.
+pub fn import_path(file_name: &str) -> PathBuf {
+    let clean = clean_file_name(file_name);
+    fs::canonicalize(&clean).unwrap_or_else(|_| PathBuf::from(clean))
+}
+
+fn find_file(file_name: String) -> Result<String, std::io::Error> {
+    fs::read_to_string(clean_file_name(&file_name))
 }
 
 fn clean_file_name(raw_name: &str) -> String {
     let clean: String = raw_name
         .to_string()
         .chars()
-        .into_iter()
         .filter(|x| x != &'\\' && x != &'"')
         .collect();
     clean.to_string()
@@ -0,0 +1,95 @@
+use super::environment::Environment;
+use super::value::Value;
+use crate::ast::statements::{self, Parameter, Stmt};
+use crate::syntax::token::Token;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+// runtime representation of a gorilla declaration, the body is shared so function values are cheap to copy around.
+// closure is the scope the declaration was evaluated in, calls run in a child of it rather than of the caller.
+pub struct Function {
+    pub name: Token,
+    pub params: Rc<Vec<Parameter>>,
+    pub rest: Option<Token>,
+    pub body: Rc<Vec<Stmt>>,
+    pub closure: Rc<RefCell<Environment>>,
+    pub is_initialiser: bool,
+    pub bound: Option<(Rc<Function>, Value)>, // the method and instance a bound method was made from.
+}
+
+impl Function {
+    pub fn new(
+        declaration: &statements::Function,
+        closure: Rc<RefCell<Environment>>,
+        is_initialiser: bool,
+    ) -> Self {
+        Self {
+            name: declaration.name.clone(),
+            params: Rc::clone(&declaration.params),
+            rest: declaration.rest.clone(),
+            body: Rc::clone(&declaration.body),
+            closure,
+            is_initialiser,
+            bound: None,
+        }
+    }
+
+    // wraps the closure in a scope where `this` is the given instance.
+    pub fn bind(self: &Rc<Self>, instance: Value) -> Function {
+        let mut scope = Environment::with_enclosing(Rc::clone(&self.closure));
+        scope.define("this".to_string(), instance.clone());
+        Function {
+            name: self.name.clone(),
+            params: Rc::clone(&self.params),
+            rest: self.rest.clone(),
+            body: Rc::clone(&self.body),
+            closure: Rc::new(RefCell::new(scope)),
+            is_initialiser: self.is_initialiser,
+            bound: Some((Rc::clone(self), instance)),
+        }
+    }
+
+    // a method is bound anew each time it is read, the results are still the same gorilla when they
+    // come from the same method and instance.
+    pub fn same(&self, other: &Function) -> bool {
+        match (&self.bound, &other.bound) {
+            (Some((m1, this1)), Some((m2, this2))) => Rc::ptr_eq(m1, m2) && this1 == this2,
+            _ => std::ptr::eq(self, other),
+        }
+    }
+}
+
+// the closure is left out as it can refer back to the function itself.
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name.lexeme)
+            .field(
+                "params",
+                &signature(&self.name.lexeme, &self.params, self.rest.as_ref()),
+            )
+            .finish()
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<gorilla {}>", self.name.lexeme)
+    }
+}
+
+// how a declaration reads in error messages, parameters with a default are marked with ?, like report(title, width?, ...rows).
+pub fn signature(name: &str, params: &[Parameter], rest: Option<&Token>) -> String {
+    let mut parts: Vec<String> = params
+        .iter()
+        .map(|param| match param.default {
+            Some(_) => format!("{}?", param.name.lexeme),
+            None => param.name.lexeme.clone(),
+        })
+        .collect();
+    if let Some(rest) = rest {
+        parts.push(format!("...{}", rest.lexeme));
+    }
+    format!("{}({})", name, parts.join(", "))
+}
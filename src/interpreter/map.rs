@@ -0,0 +1,92 @@
+use super::decimal::Decimal;
+use super::value::Value;
+use std::collections::HashMap;
+
+// the subset of values that can be used as map keys. Numbers are stored by their bits so they can be hashed,
+// -0 is folded into 0 so the two equal numbers land on the same entry. Whole Numbers become Int keys, since 1 == 1.0,
+// and Decimals become Int or Number keys when they equal one, otherwise they are stored by their reduced text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    Int(i64),
+    Number(u64),
+    Decimal(String),
+    Bool(bool),
+    None,
+}
+
+impl MapKey {
+    pub fn from_value(value: &Value) -> Option<MapKey> {
+        match value {
+            Value::String(s) => Some(MapKey::String(s.clone())),
+            Value::Int(n) => Some(MapKey::Int(*n)),
+            Value::Number(n)
+                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < -(i64::MIN as f64) =>
+            {
+                Some(MapKey::Int(*n as i64))
+            }
+            Value::Number(n) => Some(MapKey::Number((n + 0.0).to_bits())),
+            Value::Decimal(d) => match d.to_i64() {
+                Some(n) => Some(MapKey::Int(n)),
+                None if Decimal::from_f64(d.to_f64()).as_ref() == Some(d) => {
+                    MapKey::from_value(&Value::Number(d.to_f64()))
+                }
+                None => Some(MapKey::Decimal(d.reduce().to_string())),
+            },
+            Value::Bool(b) => Some(MapKey::Bool(*b)),
+            Value::None => Some(MapKey::None),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            MapKey::String(s) => Value::String(s.clone()),
+            MapKey::Int(n) => Value::Int(*n),
+            MapKey::Number(bits) => Value::Number(f64::from_bits(*bits)),
+            MapKey::Decimal(text) => Value::Decimal(Decimal::parse(text).unwrap()),
+            MapKey::Bool(b) => Value::Bool(*b),
+            MapKey::None => Value::None,
+        }
+    }
+}
+
+// keeps entries in insertion order so printing a map is deterministic.
+#[derive(Debug, Default)]
+pub struct Map {
+    entries: Vec<(MapKey, Value)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Value> {
+        self.positions.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    // overwriting a key keeps its original position.
+    pub fn insert(&mut self, key: MapKey, value: Value) {
+        match self.positions.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(MapKey, Value)> {
+        self.entries.iter()
+    }
+}
@@ -1,143 +1,1970 @@
+mod class;
+pub mod decimal;
 mod environment;
 mod file_resolver;
+mod function;
+mod map;
 pub mod run;
-mod value;
+pub(crate) mod value;
 use crate::ast::expressions::Expr;
-use crate::ast::statements::Stmt;
-use crate::errors::runtime_error::RuntimeError;
+use crate::ast::statements::{Parameter, Stmt};
+use crate::errors::runtime_error::{ErrorKind, RuntimeError};
 use crate::errors::Error;
-use crate::syntax::token::{Literal, TokenType};
+use crate::syntax::token::{Literal, Span, Token, TokenType};
+use class::{Class, Instance};
+use decimal::DecimalContext;
 use environment::Environment;
+use file_resolver::ImportError;
+use function::{signature, Function};
+use map::{Map, MapKey};
 use run::print_value;
-use value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use value::{OpError, Value};
+
+// deeper recursion is stopped with a runtime error rather than overflowing the native stack.
+const MAX_CALL_DEPTH: usize = 1000;
+// every statement and expression being run holds a frame on the native stack, calls included, so
+// this bounds the stack a script needs. run::STACK_SIZE fits it even in unoptimised builds.
+const MAX_EVALUATION_DEPTH: usize = 10_000;
 
 pub struct Interpreter {
-    env: Environment,
+    globals: Rc<RefCell<Environment>>,
+    env: Rc<RefCell<Environment>>,
+    decimal_context: DecimalContext,
+    error_class: Rc<Class>, // the Jungle of the error values that catch blocks see.
+    depth: usize,           // how many gorilla calls are running.
+    importing: Vec<PathBuf>, // the files whose imports are running, importing one again would never finish.
+    evaluating: usize,       // how many statements and expressions are part way through running.
+}
+
+// how a statement finished, break and continue travel up through nested blocks until a loop handles them,
+// return travels up until the function call that is running the body.
+#[derive(Debug, PartialEq)]
+enum Signal {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
         Self {
-            env: Environment::new(),
+            env: Rc::clone(&globals),
+            globals,
+            decimal_context: DecimalContext::default(),
+            error_class: Rc::new(Class {
+                name: "Error".to_string(),
+                superclass: None,
+                methods: HashMap::new(),
+            }),
+            depth: 0,
+            importing: Vec::new(),
+            evaluating: 0,
         }
     }
-    pub fn interpret_statements(&mut self, statements: Vec<Stmt>) {
+
+    // the scale and rounding used when a division involves a Decimal.
+    pub fn set_decimal_context(&mut self, context: DecimalContext) {
+        self.decimal_context = context;
+    }
+
+    // an uncaught error stops the script, it is reported with the calls it unwound through.
+    pub fn interpret_statements(&mut self, statements: &[Stmt]) {
+        if let Err(error) = self.execute_statements(statements) {
+            error.report();
+        }
+    }
+
+    // stops early and hands back any loop signal so the enclosing loop can act on it.
+    fn execute_statements(&mut self, statements: &[Stmt]) -> Result<Signal, RuntimeError> {
         for statement in statements {
-            self.interpret_statement(statement);
+            let signal = self.execute(statement)?;
+            if signal != Signal::Normal {
+                return Ok(signal);
+            }
         }
+        Ok(Signal::Normal)
     }
 
-    #[allow(warnings)] // compiler doesnt like interpret_expression having error variant but these get reported they occur anyway.
-    fn interpret_statement(&mut self, statement: Stmt) {
+    fn interpret_statement(&mut self, statement: &Stmt) -> Result<Signal, RuntimeError> {
         match statement {
             Stmt::Expression(e) => {
-                self.interpret_expression(e.expression);
-                return;
+                self.evaluate(&e.expression)?;
             }
             Stmt::Print(val) => {
-                let output = self.interpret_expression(val.expression);
-                print_value(output);
+                print_value(&mut std::io::stdout(), self.evaluate(&val.expression)?)
             }
             Stmt::Let(v) => {
-                let value = self.evaluate(v.initialiser);
-                self.env.define(v.name.lexeme, value);
+                let value = self.evaluate(&v.initialiser)?;
+                self.env.borrow_mut().define(v.name.lexeme.clone(), value);
             }
-            Stmt::Import(import) => {
-                let file_name = import.file_name;
-                self.handle_import(file_name);
-                return;
+            Stmt::Import(import) => self.handle_import(&import.file_name, &import.keyword)?,
+            Stmt::Block(block) => {
+                let scope = Environment::with_enclosing(Rc::clone(&self.env));
+                return self.execute_block(&block.statements, scope);
             }
+            Stmt::If(stmt) => {
+                if self.evaluate(&stmt.condition)?.is_truthy() {
+                    return self.execute(&stmt.then_branch);
+                } else if let Some(else_branch) = &stmt.else_branch {
+                    return self.execute(else_branch);
+                }
+            }
+            Stmt::While(stmt) => {
+                while self.evaluate(&stmt.condition)?.is_truthy() {
+                    match self.execute(&stmt.body)? {
+                        Signal::Break => break,
+                        Signal::Return(value) => return Ok(Signal::Return(value)),
+                        Signal::Normal | Signal::Continue => {}
+                    }
+                    // desugared for loops still run their increment after a continue.
+                    if let Some(increment) = &stmt.increment {
+                        self.evaluate(increment)?;
+                    }
+                }
+            }
+            Stmt::Break(_) => return Ok(Signal::Break),
+            Stmt::Continue(_) => return Ok(Signal::Continue),
+            Stmt::Function(declaration) => {
+                let function = Function::new(declaration, Rc::clone(&self.env), false);
+                self.env.borrow_mut().define(
+                    declaration.name.lexeme.clone(),
+                    Value::Function(Rc::new(function)),
+                );
+            }
+            Stmt::Return(stmt) => {
+                let value = match &stmt.value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::None,
+                };
+                return Ok(Signal::Return(value));
+            }
+            Stmt::Class(declaration) => {
+                let superclass = match &declaration.superclass {
+                    Some(parent) => match self.evaluate(parent)? {
+                        Value::Class(class) => Some(class),
+                        _ => {
+                            return Err(RuntimeError::new(
+                                ErrorKind::Type,
+                                format!(
+                                    "Parent of Jungle '{}' must be a Jungle",
+                                    declaration.name.lexeme
+                                ),
+                                declaration.name.span(),
+                            ))
+                        }
+                    },
+                    None => None,
+                };
+                // with a parent the methods close over an extra scope holding `super`.
+                let closure = match &superclass {
+                    Some(superclass) => {
+                        let mut scope = Environment::with_enclosing(Rc::clone(&self.env));
+                        scope.define("super".to_string(), Value::Class(Rc::clone(superclass)));
+                        Rc::new(RefCell::new(scope))
+                    }
+                    None => Rc::clone(&self.env),
+                };
+                let methods = declaration
+                    .methods
+                    .iter()
+                    .map(|method| {
+                        let is_initialiser = method.name.lexeme == "init";
+                        let function = Function::new(method, Rc::clone(&closure), is_initialiser);
+                        (method.name.lexeme.clone(), Rc::new(function))
+                    })
+                    .collect();
+                let class = Class {
+                    name: declaration.name.lexeme.clone(),
+                    superclass,
+                    methods,
+                };
+                self.env.borrow_mut().define(
+                    declaration.name.lexeme.clone(),
+                    Value::Class(Rc::new(class)),
+                );
+            }
+            Stmt::Try(stmt) => {
+                let scope = Environment::with_enclosing(Rc::clone(&self.env));
+                let result = match (self.execute_block(&stmt.body, scope), &stmt.catch_body) {
+                    (Err(error), Some(catch_body)) => {
+                        let mut scope = Environment::with_enclosing(Rc::clone(&self.env));
+                        if let Some(name) = &stmt.catch_name {
+                            scope.define(name.lexeme.clone(), self.error_value(error));
+                        }
+                        self.execute_block(catch_body, scope)
+                    }
+                    (result, _) => result,
+                };
+                // finally runs however the rest finished, its own break, continue, return or error wins.
+                return match &stmt.finally_body {
+                    Some(finally_body) => {
+                        let scope = Environment::with_enclosing(Rc::clone(&self.env));
+                        match self.execute_block(finally_body, scope)? {
+                            Signal::Normal => result,
+                            signal => Ok(signal),
+                        }
+                    }
+                    None => result,
+                };
+            }
+            Stmt::Throw(stmt) => {
+                let value = self.evaluate(&stmt.value)?;
+                // a thrown instance with a message field reports that message if nothing catches it.
+                let msg = match &value {
+                    Value::Instance(instance) => match instance.borrow().fields.get("message") {
+                        Some(message) => message.to_string(),
+                        None => value.to_string(),
+                    },
+                    _ => value.to_string(),
+                };
+                let mut error = RuntimeError::new(ErrorKind::Thrown, msg, stmt.span);
+                error.thrown = Some(value);
+                return Err(error);
+            }
+        }
+        Ok(Signal::Normal)
+    }
+
+    // runs the statements in the given scope, restoring the current one afterwards however the block finished.
+    fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        scope: Environment,
+    ) -> Result<Signal, RuntimeError> {
+        let previous = std::mem::replace(&mut self.env, Rc::new(RefCell::new(scope)));
+        let signal = self.execute_statements(statements);
+        self.env = previous;
+        signal
+    }
+
+    // the body runs in a child of the scope the function was declared in, not the caller's.
+    // arguments holds one entry per parameter, None where the parameter's default should be used.
+    fn call_function(
+        &mut self,
+        function: &Function,
+        arguments: Vec<Option<Value>>,
+        rest: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let scope = Rc::new(RefCell::new(Environment::with_enclosing(Rc::clone(
+            &function.closure,
+        ))));
+        let previous = std::mem::replace(&mut self.env, Rc::clone(&scope));
+        self.depth += 1;
+        let signal = self.execute_function(function, &scope, arguments, rest);
+        self.depth -= 1;
+        self.env = previous;
+        let signal = signal?;
+        // init always hands back the instance, even from a bare return.
+        if function.is_initialiser {
+            return Ok(function
+                .closure
+                .borrow()
+                .lookup("this")
+                .unwrap_or(Value::None));
+        }
+        match signal {
+            Signal::Return(value) => Ok(value),
+            _ => Ok(Value::None),
         }
     }
 
-    fn handle_import(&mut self, file_name: String) {
-        match file_resolver::create_statement_stream(&file_name) {
-            Ok(stmts) => self.interpret_statements(stmts),
-            Err(e) => {
-                println!("{:?}", e);
-                RuntimeError {
-                    msg: format!("Could not resolve file import: {:?}", file_name),
+    // binds the parameters in the call's scope, which is already current, and runs the body.
+    fn execute_function(
+        &mut self,
+        function: &Function,
+        scope: &Rc<RefCell<Environment>>,
+        arguments: Vec<Option<Value>>,
+        rest: Vec<Value>,
+    ) -> Result<Signal, RuntimeError> {
+        for (param, argument) in function.params.iter().zip(arguments) {
+            let value = match (argument, &param.default) {
+                (Some(value), _) => value,
+                // evaluated in the new scope, so it can use the parameters bound so far.
+                (None, Some(default)) => self.evaluate(default)?,
+                (None, None) => Value::None,
+            };
+            scope.borrow_mut().define(param.name.lexeme.clone(), value);
+        }
+        if let Some(name) = &function.rest {
+            scope.borrow_mut().define(
+                name.lexeme.clone(),
+                Value::List(Rc::new(RefCell::new(rest))),
+            );
+        }
+        self.execute_statements(&function.body)
+    }
+
+    // an error escaping the body picks up a line of stack trace naming this call.
+    fn call(
+        &mut self,
+        callee: Value,
+        arguments: Vec<Value>,
+        named: Vec<(Token, Value)>,
+        paren: &Token,
+    ) -> Result<Value, RuntimeError> {
+        let frame = |name: &str| {
+            let frame = format!("{}() called on line {}", name, paren.line);
+            move |mut error: RuntimeError| {
+                error.trace.push(frame);
+                error
+            }
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(
+                ErrorKind::Recursion,
+                format!("Maximum call depth of {} exceeded", MAX_CALL_DEPTH),
+                paren.span(),
+            ));
+        }
+        match callee {
+            Value::Function(function) => {
+                let (arguments, rest) = bind_arguments(
+                    &function.name.lexeme,
+                    &function.params,
+                    function.rest.as_ref(),
+                    arguments,
+                    named,
+                    paren,
+                )?;
+                self.call_function(&function, arguments, rest)
+                    .map_err(frame(&function.name.lexeme))
+            }
+            // a Jungle takes whatever arguments its init method does.
+            Value::Class(class) => {
+                let instance = Rc::new(RefCell::new(Instance::new(Rc::clone(&class))));
+                match class.find_method("init") {
+                    Some(init) => {
+                        let (arguments, rest) = bind_arguments(
+                            &class.name,
+                            &init.params,
+                            init.rest.as_ref(),
+                            arguments,
+                            named,
+                            paren,
+                        )?;
+                        let init = init.bind(Value::Instance(Rc::clone(&instance)));
+                        self.call_function(&init, arguments, rest)
+                            .map_err(frame(&class.name))?;
+                    }
+                    None => {
+                        bind_arguments(&class.name, &[], None, arguments, named, paren)?;
+                    }
                 }
-                .report();
+                Ok(Value::Instance(instance))
+            }
+            _ => Err(RuntimeError::new(
+                ErrorKind::Type,
+                "Can only call gorilla functions and Jungles".to_string(),
+                paren.span(),
+            )),
+        }
+    }
+
+    // imported declarations always land in the global scope, that is where the resolver expects them.
+    fn handle_import(&mut self, file_name: &str, keyword: &Token) -> Result<(), RuntimeError> {
+        let path = file_resolver::import_path(file_name);
+        let stmts = match self.importing.contains(&path) {
+            true => Err(ImportError::Cycle),
+            false => file_resolver::create_statement_stream(file_name),
+        };
+        match stmts {
+            Ok(stmts) => {
+                self.importing.push(path);
+                let previous = std::mem::replace(&mut self.env, Rc::clone(&self.globals));
+                let result = self.execute_statements(&stmts);
+                self.env = previous;
+                self.importing.pop();
+                result.map(|_| ())
             }
+            Err(e) => Err(RuntimeError::new(
+                ErrorKind::Import,
+                format!("Could not import {}: {}", file_name, e),
+                keyword.span(),
+            )),
         }
     }
 
-    fn interpret_expression(&mut self, expr: Expr) -> Result<Value, RuntimeError> {
+    // what a catch block sees. A thrown Jungle instance comes back as it was thrown, anything else
+    // is wrapped in an Error instance with message, line, column and kind fields, plus value if it was thrown.
+    fn error_value(&self, error: RuntimeError) -> Value {
+        if let Some(Value::Instance(instance)) = &error.thrown {
+            return Value::Instance(Rc::clone(instance));
+        }
+        let mut instance = Instance::new(Rc::clone(&self.error_class));
+        let fields = &mut instance.fields;
+        fields.insert("message".to_string(), Value::String(error.msg));
+        fields.insert("line".to_string(), Value::Int(error.span.line as i64));
+        fields.insert("column".to_string(), Value::Int(error.span.column as i64));
+        fields.insert("kind".to_string(), Value::String(error.kind.to_string()));
+        if let Some(value) = error.thrown {
+            fields.insert("value".to_string(), value);
+        }
+        Value::Instance(Rc::new(RefCell::new(instance)))
+    }
+
+    fn interpret_expression(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
         match expr {
-            Expr::Literal(literal) => match literal.value {
-                Literal::String(s) => Ok(Value::String(s)),
-                Literal::Number(n) => Ok(Value::Number(n)),
-                Literal::Bool(b) => Ok(Value::Bool(b)),
+            Expr::Literal(literal) => match &literal.value {
+                Literal::String(s) => Ok(Value::String(s.clone())),
+                Literal::Int(n) => Ok(Value::Int(*n)),
+                Literal::Number(n) => Ok(Value::Number(*n)),
+                Literal::Decimal(d) => Ok(Value::Decimal(d.clone())),
+                Literal::Bool(b) => Ok(Value::Bool(*b)),
                 Literal::None => Ok(Value::None),
             },
-            Expr::Grouping(grouping) => Ok(self.evaluate(*grouping.expression)), // need to dereference with * as grouping.expression is inside a Box<T> smart pointer and we pass by value into evaluate.
+            Expr::Grouping(grouping) => self.evaluate(&grouping.expression),
             Expr::Unary(unary) => {
-                let right = self.evaluate(*unary.right);
+                let right = self.evaluate(&unary.right)?;
                 match unary.operator.token_type {
-                    TokenType::Minus => {
-                        if let Some(num) = -right.clone() {
-                            return Ok(num);
-                        } else {
-                            Err(RuntimeError {
-                                msg: "Invalid negation, can only negate type Number.".to_string(),
-                            })
-                        }
-                    }
+                    TokenType::Minus => compute(
+                        -right,
+                        &unary.operator,
+                        "Invalid negation, can only negate types Int, Number and Decimal."
+                            .to_string(),
+                    ),
                     TokenType::Bang => Ok(!right),
-                    _ => Err(RuntimeError {
-                        msg: "Invalid unary operation".to_string(),
-                    }),
+                    _ => Err(RuntimeError::new(
+                        ErrorKind::Type,
+                        "Invalid unary operation".to_string(),
+                        unary.operator.span(),
+                    )),
                 }
             }
             Expr::Binary(binary) => {
-                let left = self.evaluate(*binary.left);
-                let right = self.evaluate(*binary.right);
-                match binary.operator.token_type {
-                    TokenType::Minus => {
-                        compute(left - right, "Can only subtract type Number".to_string())
-                    }
-                    TokenType::Plus => compute(
-                        left + right,
-                        "Can only add literals of same type. Supported types: Number, String"
-                            .to_string(),
+                let left = self.evaluate(&binary.left)?;
+                let right = self.evaluate(&binary.right)?;
+                self.binary(left, &binary.operator, right)
+            }
+            Expr::Variable(var) => self.look_up_variable(&var.name, var.depth.get()),
+            Expr::Logical(logical) => {
+                // the operand that decides the result is returned as is, the right side only runs when needed.
+                let left = self.evaluate(&logical.left)?;
+                let decided = match logical.operator.token_type {
+                    TokenType::Or => left.is_truthy(),
+                    _ => !left.is_truthy(),
+                };
+                if decided {
+                    Ok(left)
+                } else {
+                    self.evaluate(&logical.right)
+                }
+            }
+            Expr::Conditional(conditional) => {
+                if self.evaluate(&conditional.condition)?.is_truthy() {
+                    self.evaluate(&conditional.then_branch)
+                } else {
+                    self.evaluate(&conditional.else_branch)
+                }
+            }
+            // unlike or, only null falls through, so 0, "" and false are kept.
+            Expr::Coalesce(coalesce) => match self.evaluate(&coalesce.left)? {
+                Value::None => self.evaluate(&coalesce.right),
+                left => Ok(left),
+            },
+            Expr::Call(call) => {
+                let callee = self.evaluate(&call.callee)?;
+                let arguments = call
+                    .arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+                let named = call
+                    .named
+                    .iter()
+                    .map(|(name, argument)| Ok((name.clone(), self.evaluate(argument)?)))
+                    .collect::<Result<Vec<(Token, Value)>, RuntimeError>>()?;
+                self.call(callee, arguments, named, &call.paren)
+            }
+            Expr::Get(get) => match self.evaluate(&get.object)? {
+                Value::Instance(instance) => Instance::get(&instance, &get.name),
+                _ => Err(RuntimeError::new(
+                    ErrorKind::Type,
+                    "Only Jungle instances have properties".to_string(),
+                    get.name.span(),
+                )),
+            },
+            Expr::Set(set) => match self.evaluate(&set.object)? {
+                Value::Instance(instance) => {
+                    let (value, result) = match &set.operator {
+                        Some(operator) => {
+                            let current = Instance::get(&instance, &set.name)?;
+                            self.compound(current, operator, &set.value, set.postfix)?
+                        }
+                        None => self.assigned(&set.value)?,
+                    };
+                    instance.borrow_mut().set(&set.name, value);
+                    Ok(result)
+                }
+                _ => Err(RuntimeError::new(
+                    ErrorKind::Type,
+                    "Only Jungle instances have fields".to_string(),
+                    set.name.span(),
+                )),
+            },
+            Expr::This(this) => self.look_up_variable(&this.keyword, this.depth.get()),
+            Expr::Super(sup) => {
+                // `this` is bound in the scope just inside the one holding `super`.
+                let (superclass, instance) = match sup.depth.get() {
+                    Some(distance) if distance > 0 => (
+                        Environment::get_at(&self.env, distance, "super"),
+                        Environment::get_at(&self.env, distance - 1, "this"),
                     ),
-                    TokenType::Slash => compute(left / right, "Division error.".to_string()),
-                    TokenType::Star => {
-                        compute(left * right, "Can only multiply type Number".to_string())
+                    _ => (None, None),
+                };
+                let method = match (superclass, instance) {
+                    (Some(Value::Class(superclass)), Some(instance)) => superclass
+                        .find_method(&sup.method.lexeme)
+                        .map(|method| method.bind(instance)),
+                    _ => {
+                        return Err(RuntimeError::new(
+                            ErrorKind::Name,
+                            "Can't use super outside of a Jungle with a parent".to_string(),
+                            sup.keyword.span(),
+                        ))
                     }
-                    TokenType::Greater => Ok(Value::Bool(left > right)),
-                    TokenType::GreaterEqual => Ok(Value::Bool(left >= right)),
-                    TokenType::Less => Ok(Value::Bool(left < right)),
-                    TokenType::LessEqual => Ok(Value::Bool(left <= right)),
-                    TokenType::EqualEqual => Ok(Value::Bool(left == right)),
-                    TokenType::BangEqual => Ok(Value::Bool(left != right)),
-                    _ => Err(RuntimeError {
-                        msg: "dont have that feature yet".to_string(),
-                    }),
-                }
-            }
-            Expr::Variable(var) => {
-                if let Some(value) = self.env.get_value(var.name) {
-                    Ok(value)
-                } else {
-                    Err(RuntimeError {
-                        msg: "Undefined variable".to_string(),
-                    })
+                };
+                match method {
+                    Some(method) => Ok(Value::Function(Rc::new(method))),
+                    None => Err(RuntimeError::new(
+                        ErrorKind::Property,
+                        format!("Undefined property '{}'", sup.method.lexeme),
+                        sup.method.span(),
+                    )),
                 }
             }
+            Expr::List(list) => {
+                let elements = list
+                    .elements
+                    .iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
+            }
+            // every part goes through the value's display conversion, so strings are not quoted.
+            Expr::Interpolation(interpolation) => {
+                let mut text = String::new();
+                for part in interpolation.parts.iter() {
+                    text.push_str(&self.evaluate(part)?.to_string());
+                }
+                Ok(Value::String(text))
+            }
+            Expr::Lambda(lambda) => Ok(Value::Function(Rc::new(Function::new(
+                &lambda.function,
+                Rc::clone(&self.env),
+                false,
+            )))),
+            Expr::Map(literal) => {
+                let mut map = Map::new();
+                for (key, value) in literal.entries.iter() {
+                    let key_value = self.evaluate(key)?;
+                    let key = map_key(&key_value, &literal.brace)?;
+                    let value = self.evaluate(value)?;
+                    map.insert(key, value);
+                }
+                Ok(Value::Map(Rc::new(RefCell::new(map))))
+            }
+            Expr::Index(index) => {
+                let object = self.evaluate(&index.object)?;
+                let position = self.evaluate(&index.index)?;
+                index_value(&object, &position, &index.bracket)
+            }
+            Expr::IndexSet(index) => {
+                let object = self.evaluate(&index.object)?;
+                let position = self.evaluate(&index.index)?;
+                let (value, result) = match &index.operator {
+                    Some(operator) => {
+                        let current = index_value(&object, &position, &index.bracket)?;
+                        self.compound(current, operator, &index.value, index.postfix)?
+                    }
+                    None => self.assigned(&index.value)?,
+                };
+                match object {
+                    Value::List(elements) => {
+                        let mut elements = elements.borrow_mut();
+                        let i = list_index(&position, elements.len(), &index.bracket)?;
+                        elements[i] = value;
+                        Ok(result)
+                    }
+                    Value::Map(map) => {
+                        let key = map_key(&position, &index.bracket)?;
+                        map.borrow_mut().insert(key, value);
+                        Ok(result)
+                    }
+                    _ => Err(not_indexable(&index.bracket)),
+                }
+            }
+            Expr::Assign(assign) => {
+                let (value, result) = match &assign.operator {
+                    Some(operator) => {
+                        let current = self.look_up_variable(&assign.name, assign.depth.get())?;
+                        self.compound(current, operator, &assign.value, assign.postfix)?
+                    }
+                    None => self.assigned(&assign.value)?,
+                };
+                match assign.depth.get() {
+                    Some(distance) => {
+                        Environment::assign_at(&self.env, distance, &assign.name, value)
+                    }
+                    None => self.globals.borrow_mut().assign(&assign.name, value)?,
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    // an assignment stores its value and also gives it back as its result.
+    fn assigned(&mut self, value: &Expr) -> Result<(Value, Value), RuntimeError> {
+        let value = self.evaluate(value)?;
+        Ok((value.clone(), value))
+    }
+
+    // a compound assignment stores current combined with the value, x++ and x-- give back current instead.
+    fn compound(
+        &mut self,
+        current: Value,
+        operator: &Token,
+        value: &Expr,
+        postfix: bool,
+    ) -> Result<(Value, Value), RuntimeError> {
+        let right = self.evaluate(value)?;
+        let updated = self.binary(current.clone(), operator, right)?;
+        let result = if postfix { current } else { updated.clone() };
+        Ok((updated, result))
+    }
+
+    // also used by compound assignment, whose operator token has the type of the plain operator.
+    fn binary(&self, left: Value, operator: &Token, right: Value) -> Result<Value, RuntimeError> {
+        match operator.token_type {
+            TokenType::Minus => compute(
+                left - right,
+                operator,
+                "Can only subtract types Int, Number and Decimal".to_string(),
+            ),
+            TokenType::Plus => compute(
+                left + right,
+                operator,
+                "Can only add literals of same type. Supported types: Int, Number, Decimal, String"
+                    .to_string(),
+            ),
+            TokenType::Slash => compute(
+                left.divide(right, self.decimal_context),
+                operator,
+                "Division error.".to_string(),
+            ),
+            TokenType::TildeSlash => {
+                compute(left.int_div(right), operator, "Division error.".to_string())
+            }
+            TokenType::Percent => compute(
+                left % right,
+                operator,
+                "Can only take the remainder of types Int, Number and Decimal".to_string(),
+            ),
+            TokenType::StarStar => compute(
+                left.power(right, self.decimal_context),
+                operator,
+                "Can only raise types Int, Number and Decimal to a power, Decimals only to whole powers"
+                    .to_string(),
+            ),
+            TokenType::Ampersand => compute(left & right, operator, bitwise_error("&")),
+            TokenType::Pipe => compute(left | right, operator, bitwise_error("|")),
+            TokenType::Caret => compute(left ^ right, operator, bitwise_error("^")),
+            TokenType::LessLess => compute(
+                left << right,
+                operator,
+                "Can only shift whole numbers, by a non-negative amount".to_string(),
+            ),
+            TokenType::GreaterGreater => compute(
+                left >> right,
+                operator,
+                "Can only shift whole numbers, by a non-negative amount".to_string(),
+            ),
+            TokenType::Star => compute(
+                left * right,
+                operator,
+                "Can only multiply types Int, Number and Decimal".to_string(),
+            ),
+            TokenType::Greater => Ok(Value::Bool(left > right)),
+            TokenType::GreaterEqual => Ok(Value::Bool(left >= right)),
+            TokenType::Less => Ok(Value::Bool(left < right)),
+            TokenType::LessEqual => Ok(Value::Bool(left <= right)),
+            TokenType::EqualEqual => Ok(Value::Bool(left == right)),
+            TokenType::BangEqual => Ok(Value::Bool(left != right)),
+            _ => Err(RuntimeError::new(
+                ErrorKind::Type,
+                "dont have that feature yet".to_string(),
+                operator.span(),
+            )),
         }
     }
 
+    fn look_up_variable(&self, name: &Token, depth: Option<usize>) -> Result<Value, RuntimeError> {
+        let value = match depth {
+            Some(distance) => Environment::get_at(&self.env, distance, &name.lexeme),
+            None => self.globals.borrow().get_value(name),
+        };
+        value.ok_or_else(|| {
+            RuntimeError::new(
+                ErrorKind::Name,
+                format!("Undefined variable '{}'", name.lexeme),
+                name.span(),
+            )
+        })
+    }
+
     // pass it back to interpret_expression (use for recursion) usually pass in nested sub expression.
-    fn evaluate(&mut self, expr: Expr) -> Value {
-        self.interpret_expression(expr).unwrap()
+    fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.deeper(expr.span())?;
+        let value = self.interpret_expression(expr);
+        self.evaluating -= 1;
+        value
+    }
+
+    // the same for statements, everything that runs one goes through here.
+    fn execute(&mut self, statement: &Stmt) -> Result<Signal, RuntimeError> {
+        self.deeper(statement.span())?;
+        let signal = self.interpret_statement(statement);
+        self.evaluating -= 1;
+        signal
+    }
+
+    fn deeper(&mut self, span: Span) -> Result<(), RuntimeError> {
+        if self.evaluating == MAX_EVALUATION_DEPTH {
+            return Err(RuntimeError::new(
+                ErrorKind::Recursion,
+                format!(
+                    "Maximum evaluation depth of {} exceeded",
+                    MAX_EVALUATION_DEPTH
+                ),
+                span,
+            ));
+        }
+        self.evaluating += 1;
+        Ok(())
+    }
+}
+
+// matches a call's arguments up with the parameters, giving one entry per parameter and the extras for the rest
+// parameter. Every mismatch is found here, before any argument is bound or default evaluated.
+fn bind_arguments(
+    name: &str,
+    params: &[Parameter],
+    rest: Option<&Token>,
+    positional: Vec<Value>,
+    named: Vec<(Token, Value)>,
+    paren: &Token,
+) -> Result<(Vec<Option<Value>>, Vec<Value>), RuntimeError> {
+    let position = |argument: &Token| {
+        params
+            .iter()
+            .position(|param| param.name.lexeme == argument.lexeme)
+    };
+    let problem = if positional.len() > params.len() && rest.is_none() {
+        Some("Too many arguments".to_string())
+    } else if let Some((argument, _)) = named
+        .iter()
+        .find(|(argument, _)| position(argument).is_none())
+    {
+        Some(format!("Unknown argument '{}'", argument.lexeme))
+    } else if let Some(argument) = named.iter().enumerate().find_map(|(i, (argument, _))| {
+        // given positionally already, or named a second time.
+        let index = position(argument)?;
+        let repeated = index < positional.len()
+            || named[..i]
+                .iter()
+                .any(|(earlier, _)| earlier.lexeme == argument.lexeme);
+        repeated.then_some(argument)
+    }) {
+        Some(format!("Argument '{}' given twice", argument.lexeme))
+    } else {
+        params.iter().enumerate().find_map(|(i, param)| {
+            let given = i < positional.len()
+                || named
+                    .iter()
+                    .any(|(argument, _)| argument.lexeme == param.name.lexeme);
+            (!given && param.default.is_none())
+                .then(|| format!("Missing argument '{}'", param.name.lexeme))
+        })
+    };
+    if let Some(problem) = problem {
+        let mut got: Vec<String> = positional
+            .iter()
+            .map(|v| v.type_name().to_string())
+            .collect();
+        got.extend(
+            named
+                .iter()
+                .map(|(argument, value)| format!("{}: {}", argument.lexeme, value.type_name())),
+        );
+        return Err(RuntimeError::new(
+            ErrorKind::Argument,
+            format!(
+                "{}, expected {} but got {}({})",
+                problem,
+                signature(name, params, rest),
+                name,
+                got.join(", ")
+            ),
+            paren.span(),
+        ));
+    }
+
+    let mut arguments: Vec<Option<Value>> = params.iter().map(|_| None).collect();
+    let mut extra = Vec::new();
+    for (i, value) in positional.into_iter().enumerate() {
+        match arguments.get_mut(i) {
+            Some(slot) => *slot = Some(value),
+            None => extra.push(value),
+        }
+    }
+    for (argument, value) in named {
+        if let Some(index) = position(&argument) {
+            arguments[index] = Some(value);
+        }
+    }
+    Ok((arguments, extra))
+}
+
+// list indices have to be whole numbers inside the list.
+fn list_index(index: &Value, len: usize, bracket: &Token) -> Result<usize, RuntimeError> {
+    let n = match index {
+        Value::Int(n) => Some(*n as f64),
+        Value::Number(n) if n.fract() == 0.0 => Some(*n),
+        _ => None,
+    };
+    match n {
+        Some(n) => {
+            if n >= 0.0 && (n as usize) < len {
+                Ok(n as usize)
+            } else {
+                Err(RuntimeError::new(
+                    ErrorKind::Index,
+                    format!("Index {} out of range for list of length {}", n, len),
+                    bracket.span(),
+                ))
+            }
+        }
+        _ => Err(RuntimeError::new(
+            ErrorKind::Type,
+            format!("List index must be an integer, got {}", index.repr()),
+            bracket.span(),
+        )),
+    }
+}
+
+// a missing map key reads as null.
+fn index_value(object: &Value, position: &Value, bracket: &Token) -> Result<Value, RuntimeError> {
+    match object {
+        Value::List(elements) => {
+            let elements = elements.borrow();
+            let i = list_index(position, elements.len(), bracket)?;
+            Ok(elements[i].clone())
+        }
+        Value::Map(map) => {
+            let key = map_key(position, bracket)?;
+            Ok(map.borrow().get(&key).cloned().unwrap_or(Value::None))
+        }
+        _ => Err(not_indexable(bracket)),
     }
 }
 
-fn compute(result: Option<Value>, msg: String) -> Result<Value, RuntimeError> {
-    match result {
-        Some(res) => Ok(res),
-        None => Err(RuntimeError { msg }),
+fn map_key(key: &Value, token: &Token) -> Result<MapKey, RuntimeError> {
+    MapKey::from_value(key).ok_or_else(|| {
+        RuntimeError::new(
+            ErrorKind::Type,
+            format!(
+                "Map keys must be String, Int, Number, Decimal, Bool or Null, got {}",
+                key.type_name()
+            ),
+            token.span(),
+        )
+    })
+}
+
+fn not_indexable(bracket: &Token) -> RuntimeError {
+    RuntimeError::new(
+        ErrorKind::Type,
+        "Can only index into lists and maps".to_string(),
+        bracket.span(),
+    )
+}
+
+fn bitwise_error(operator: &str) -> String {
+    format!(
+        "Bitwise {} needs whole numbers, a Number or Decimal with a fractional part can't be used",
+        operator
+    )
+}
+
+// msg explains a type mismatch, overflow and division by zero have their own messages.
+fn compute(
+    result: Result<Value, OpError>,
+    operator: &Token,
+    msg: String,
+) -> Result<Value, RuntimeError> {
+    let (kind, msg) = match result {
+        Ok(res) => return Ok(res),
+        Err(OpError::Type) => (ErrorKind::Type, msg),
+        Err(OpError::Overflow) => (ErrorKind::Overflow, "Integer overflow.".to_string()),
+        Err(OpError::DivisionByZero) => {
+            (ErrorKind::DivisionByZero, "Division by zero.".to_string())
+        }
+    };
+    Err(RuntimeError::new(kind, msg, operator.span()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Interpreter, RuntimeError, Stmt, Value};
+    use crate::ast::parser::Parser;
+    use crate::ast::resolver::Resolver;
+    use crate::syntax::scanner::Scanner;
+
+    // goes through the same scan, parse and resolve steps as a script, failing the test on any error.
+    fn parse(source: &str) -> Vec<Stmt> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        let mut parser = Parser::new(scanner.tokens);
+        let statements = parser.parse();
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        let mut resolver = Resolver::new();
+        resolver.resolve(&statements);
+        assert!(resolver.errors.is_empty(), "{:?}", resolver.errors);
+        statements
+    }
+
+    fn interpret(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret_statements(&parse(source));
+        interpreter
+    }
+
+    // runs setup, then source, which must stop with a runtime error.
+    fn expression_error(setup: &str, source: &str) -> RuntimeError {
+        let mut interpreter = interpret(setup);
+        interpreter.execute_statements(&parse(source)).unwrap_err()
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> Option<Value> {
+        interpreter.env.borrow().values.get(name).cloned()
+    }
+
+    fn global_text(interpreter: &Interpreter, name: &str) -> String {
+        match global(interpreter, name) {
+            Some(value) => value.to_string(),
+            None => panic!("{} is not defined", name),
+        }
+    }
+
+    #[test]
+    fn test_block_scope() {
+        let interpreter = interpret(
+            r#"
+            let a = "outer";
+            {
+                let a = "inner";
+                let temp = 1;
+                {
+                    let seenInner = a;
+                }
+            }
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "a"),
+            Some(Value::String("outer".to_string()))
+        );
+        assert!(global(&interpreter, "temp").is_none());
+        assert!(global(&interpreter, "seenInner").is_none());
+        assert!(interpreter.env.borrow().enclosing.is_none());
+    }
+
+    #[test]
+    fn test_assignment() {
+        let interpreter = interpret(
+            r#"
+            let total = 0;
+            let count = 0;
+            total = total + 5;
+            {
+                let count = 10;
+                count = count + 1;
+                total = total + count;
+            }
+            let a = 0;
+            let b = 0;
+            a = b = 3;
+            "#,
+        );
+        assert_eq!(global(&interpreter, "total"), Some(Value::Int(16)));
+        assert_eq!(global(&interpreter, "count"), Some(Value::Int(0)));
+        assert_eq!(global(&interpreter, "a"), Some(Value::Int(3)));
+        assert_eq!(global(&interpreter, "b"), Some(Value::Int(3)));
+    }
+
+    #[test]
+    fn test_if_else() {
+        let interpreter = interpret(
+            r#"
+            let amount = 150;
+            let discount = 0;
+            if (amount > 100) discount = 0.2; else discount = 0.1;
+            let tier = "none";
+            if (amount < 50) {
+                tier = "small";
+            } else if (amount < 100) {
+                tier = "medium";
+            } else {
+                tier = "large";
+            }
+            let empty = "unchanged";
+            if ("") empty = "changed";
+            if (0) empty = "changed";
+            if (null) empty = "changed";
+            "#,
+        );
+        assert_eq!(global(&interpreter, "discount"), Some(Value::Number(0.2)));
+        assert_eq!(
+            global(&interpreter, "tier"),
+            Some(Value::String("large".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "empty"),
+            Some(Value::String("unchanged".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_while_break_continue() {
+        let interpreter = interpret(
+            r#"
+            let i = 0;
+            let sum = 0;
+            while (true) {
+                i = i + 1;
+                if (i > 10) break;
+                {
+                    let small = i < 6;
+                    if (small) {
+                        continue;
+                    }
+                }
+                sum = sum + i;
+            }
+            "#,
+        );
+        assert_eq!(global(&interpreter, "i"), Some(Value::Int(11)));
+        assert_eq!(global(&interpreter, "sum"), Some(Value::Int(40)));
+        assert!(interpreter.env.borrow().enclosing.is_none());
+    }
+
+    #[test]
+    fn test_for_loop() {
+        let interpreter = interpret(
+            r#"
+            let total = 0;
+            let i = 100;
+            for (let i = 0; i < 10; i = i + 1) {
+                if (i == 3) continue;
+                if (i == 6) break;
+                total = total + i;
+            }
+            let n = 0;
+            for (; n < 4;) n = n + 1;
+            "#,
+        );
+        assert_eq!(global(&interpreter, "total"), Some(Value::Int(12)));
+        assert_eq!(global(&interpreter, "i"), Some(Value::Int(100)));
+        assert_eq!(global(&interpreter, "n"), Some(Value::Int(4)));
+    }
+
+    #[test]
+    fn test_logical_operators() {
+        let interpreter = interpret(
+            r#"
+            let calls = 0;
+            let name = null or "guest";
+            let first = "a" or (calls = calls + 1);
+            let skipped = false and (calls = calls + 1);
+            let last = 1 and "yes";
+            let mixed = false or 0 and true or "fallback";
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "name"),
+            Some(Value::String("guest".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "first"),
+            Some(Value::String("a".to_string()))
+        );
+        assert_eq!(global(&interpreter, "skipped"), Some(Value::Bool(false)));
+        assert_eq!(
+            global(&interpreter, "last"),
+            Some(Value::String("yes".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "mixed"),
+            Some(Value::String("fallback".to_string()))
+        );
+        assert_eq!(global(&interpreter, "calls"), Some(Value::Int(0)));
+    }
+
+    #[test]
+    fn test_functions() {
+        let interpreter = interpret(
+            r#"
+            gorilla fib(n) {
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+            }
+            gorilla firstOver(limit) {
+                for (let i = 0; i < 100; i = i + 1) {
+                    if (i > limit) {
+                        return i;
+                    }
+                }
+            }
+            gorilla nothing() {}
+            let result = fib(10);
+            let over = firstOver(41);
+            let empty = nothing();
+            let alias = fib;
+            let same = alias == fib;
+            let different = fib == firstOver;
+            "#,
+        );
+        assert_eq!(global(&interpreter, "result"), Some(Value::Int(55)));
+        assert_eq!(global(&interpreter, "over"), Some(Value::Int(42)));
+        assert_eq!(global(&interpreter, "empty"), Some(Value::None));
+        assert_eq!(global(&interpreter, "same"), Some(Value::Bool(true)));
+        assert_eq!(global(&interpreter, "different"), Some(Value::Bool(false)));
+        match global(&interpreter, "alias") {
+            Some(Value::Function(f)) => assert_eq!(f.to_string(), "<gorilla fib>"),
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_closures() {
+        let interpreter = interpret(
+            r#"
+            gorilla makeCounter() {
+                let count = 0;
+                gorilla increment() {
+                    count = count + 1;
+                    return count;
+                }
+                return increment;
+            }
+            let counter = makeCounter();
+            let other = makeCounter();
+            counter();
+            counter();
+            let third = counter();
+            let fresh = other();
+
+            let scale = 2;
+            gorilla scaled(n) {
+                return n * scale;
+            }
+            gorilla shadowed() {
+                let scale = 10;
+                return scaled(3);
+            }
+            let six = shadowed();
+            "#,
+        );
+        assert_eq!(global(&interpreter, "third"), Some(Value::Int(3)));
+        assert_eq!(global(&interpreter, "fresh"), Some(Value::Int(1)));
+        assert_eq!(global(&interpreter, "six"), Some(Value::Int(6)));
+    }
+
+    #[test]
+    fn test_classes() {
+        let interpreter = interpret(
+            r#"
+            Jungle Account {
+                init(owner, balance) {
+                    this.owner = owner;
+                    this.balance = balance;
+                }
+                deposit(amount) {
+                    this.balance = this.balance + amount;
+                    return this;
+                }
+                describe() {
+                    return this.owner + " account";
+                }
+            }
+            Jungle Savings < Account {
+                init(owner, balance, rate) {
+                    super.init(owner, balance);
+                    this.rate = rate;
+                }
+                describe() {
+                    return "savings " + super.describe();
+                }
+            }
+            let account = Savings("Ada", 100, 0.05);
+            account.deposit(50).deposit(25);
+            let balance = account.balance;
+            let description = account.describe();
+            let deposit = account.deposit;
+            deposit(5);
+            let after = account.balance;
+            let reinit = account.init("Bob", 1, 0) == account;
+            account.describe = "shadowed";
+            let shadowed = account.describe;
+            let isSame = account == account;
+            let isOther = account == Savings("Ada", 100, 0.05);
+            "#,
+        );
+        assert_eq!(global(&interpreter, "balance"), Some(Value::Int(175)));
+        assert_eq!(
+            global(&interpreter, "description"),
+            Some(Value::String("savings Ada account".to_string()))
+        );
+        assert_eq!(global(&interpreter, "after"), Some(Value::Int(180)));
+        assert_eq!(global(&interpreter, "reinit"), Some(Value::Bool(true)));
+        assert_eq!(
+            global(&interpreter, "shadowed"),
+            Some(Value::String("shadowed".to_string()))
+        );
+        assert_eq!(global(&interpreter, "isSame"), Some(Value::Bool(true)));
+        assert_eq!(global(&interpreter, "isOther"), Some(Value::Bool(false)));
+        match global(&interpreter, "account") {
+            Some(Value::Instance(instance)) => {
+                assert_eq!(instance.borrow().to_string(), "<Savings instance>")
+            }
+            other => panic!("expected an instance, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bound_method_identity() {
+        let interpreter = interpret(
+            r#"
+            Jungle Animal { speak() { return "..."; } eat() { return "food"; } }
+            Jungle Dog < Animal {
+                speak() { return "woof"; }
+                parentSpeak() { return super.speak; }
+            }
+            let dog = Dog();
+            let kept = dog.speak;
+            let same = dog.speak == dog.speak;
+            let stored = kept == dog.speak;
+            let otherDog = dog.speak == Dog().speak;
+            let otherMethod = dog.speak == dog.eat;
+            let overridden = dog.parentSpeak() == dog.speak;
+            let inherited = dog.parentSpeak() == Animal().speak;
+            "#,
+        );
+        let results = [
+            "same",
+            "stored",
+            "otherDog",
+            "otherMethod",
+            "overridden",
+            "inherited",
+        ]
+        .map(|name| global_text(&interpreter, name));
+        assert_eq!(
+            results,
+            ["true", "true", "false", "false", "false", "false"]
+        );
+    }
+
+    #[test]
+    fn test_closure_keeps_resolved_binding() {
+        let interpreter = interpret(
+            r#"
+            let a = "global";
+            let first = null;
+            let second = null;
+            {
+                gorilla showA() {
+                    return a;
+                }
+                first = showA();
+                let a = "block";
+                second = showA();
+            }
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "first"),
+            Some(Value::String("global".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "second"),
+            Some(Value::String("global".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let interpreter = interpret(
+            r#"
+            let prices = [10, 20, 30,];
+            let alias = prices;
+            alias[1] = 25;
+            let second = prices[1];
+            let nested = [[1, 2], [3]];
+            nested[0][1] = prices;
+            let deep = nested[0][1][2];
+            let total = 0;
+            for (let i = 0; i < 3; i = i + 1) total = total + prices[i];
+            let same = [1, "a"] == [1, "a"];
+            let empty = [];
+            "#,
+        );
+        assert_eq!(global(&interpreter, "second"), Some(Value::Int(25)));
+        assert_eq!(global(&interpreter, "deep"), Some(Value::Int(30)));
+        assert_eq!(global(&interpreter, "total"), Some(Value::Int(65)));
+        assert_eq!(global(&interpreter, "same"), Some(Value::Bool(true)));
+        assert_eq!(
+            global(&interpreter, "prices").unwrap().to_string(),
+            "[10, 25, 30]"
+        );
+        assert_eq!(global(&interpreter, "empty").unwrap().to_string(), "[]");
+    }
+
+    #[test]
+    fn test_cyclic_lists() {
+        let interpreter = interpret(
+            r#"
+            let a = [0];
+            a[0] = a;
+            print a;
+            let shared = [1];
+            let twice = [shared, shared];
+            let b = [0]; b[0] = b;
+            let c = [0]; c[0] = c;
+            let same = b == c;
+            let d = [1, 0]; d[1] = d;
+            let e = [2, 0]; e[1] = e;
+            let different = d == e;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "a"), "[[...]]");
+        // only a list inside itself is elided, not one that appears twice.
+        assert_eq!(global_text(&interpreter, "twice"), "[[1], [1]]");
+        assert_eq!(global_text(&interpreter, "same"), "true");
+        assert_eq!(global_text(&interpreter, "different"), "false");
+    }
+
+    #[test]
+    fn test_list_index_errors() {
+        let setup = "let xs = [1, 2];";
+        assert_eq!(
+            expression_error(setup, "xs[2];").to_string(),
+            "Index 2 out of range for list of length 2 on line 1"
+        );
+        assert_eq!(
+            expression_error(setup, "\nxs[0.5] = 1;").to_string(),
+            "List index must be an integer, got 0.5 on line 2"
+        );
+    }
+
+    #[test]
+    fn test_maps() {
+        let interpreter = interpret(
+            r#"
+            let config = {"currency": "GBP", "rate": 0.2, 1: true, null: "none",};
+            let alias = config;
+            alias["rate"] = 0.25;
+            config["extra"] = [1, 2];
+            let rate = config["rate"];
+            let missing = config["missing"];
+            let byNumber = config[1];
+            let empty = {};
+            let same = {"a": 1, "b": 2} == {"b": 2, "a": 1};
+            {
+                let scoped = {"inner": {"x": 1}};
+                scoped["inner"]["x"] = 2;
+                rate = rate + scoped["inner"]["x"];
+            }
+            "#,
+        );
+        assert_eq!(global(&interpreter, "rate"), Some(Value::Number(2.25)));
+        assert_eq!(global(&interpreter, "missing"), Some(Value::None));
+        assert_eq!(global(&interpreter, "byNumber"), Some(Value::Bool(true)));
+        assert_eq!(global(&interpreter, "same"), Some(Value::Bool(true)));
+        assert_eq!(
+            global(&interpreter, "config").unwrap().to_string(),
+            r#"{"currency": "GBP", "rate": 0.25, 1: true, Null: "none", "extra": [1, 2]}"#
+        );
+        assert_eq!(global(&interpreter, "empty").unwrap().to_string(), "{}");
+    }
+
+    #[test]
+    fn test_cyclic_maps() {
+        let interpreter = interpret(
+            r#"
+            let m = {};
+            m["s"] = m;
+            let shown = "${m}";
+            let l = [m];
+            m["l"] = l;
+            let b = {"n": 1}; b["self"] = b;
+            let c = {"n": 1}; c["self"] = c;
+            let same = b == c;
+            c["n"] = 2;
+            let different = b == c;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "shown"), r#"{"s": {...}}"#);
+        assert_eq!(
+            global_text(&interpreter, "l"),
+            r#"[{"s": {...}, "l": [...]}]"#
+        );
+        assert_eq!(global_text(&interpreter, "same"), "true");
+        assert_eq!(global_text(&interpreter, "different"), "false");
+    }
+
+    #[test]
+    fn test_unhashable_map_key() {
+        assert_eq!(
+            expression_error("let m = {};", "\nm[[1]] = 2;").to_string(),
+            "Map keys must be String, Int, Number, Decimal, Bool or Null, got List on line 2"
+        );
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let interpreter = interpret(
+            r#"
+            let amount = 100;
+            let discount = 20;
+            let item = "pear";
+            let total = "Total: ${amount - discount}";
+            let nested = "${item}s: ${"${[1, 2][0] + 1} of ${ {"n": 3}["n"] }"}!";
+            let plain = "no ${"interpolation"} needed";
+            "#,
+        );
+        assert_eq!(
+            global(&interpreter, "total"),
+            Some(Value::String("Total: 80".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "nested"),
+            Some(Value::String("pears: 2 of 3!".to_string()))
+        );
+        assert_eq!(
+            global(&interpreter, "plain"),
+            Some(Value::String("no interpolation needed".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_integers() {
+        let interpreter = interpret(
+            r#"
+            let count = 7;
+            let half = count / 2;
+            let whole = count ~/ 2;
+            let left = count % 3;
+            let mixed = count + 0.5;
+            let items = {1: "one"};
+            let one = items[1.0];
+            "#,
+        );
+        assert!(matches!(global(&interpreter, "count"), Some(Value::Int(7))));
+        assert!(matches!(global(&interpreter, "half"), Some(Value::Number(n)) if n == 3.5));
+        assert!(matches!(global(&interpreter, "whole"), Some(Value::Int(3))));
+        assert!(matches!(global(&interpreter, "left"), Some(Value::Int(1))));
+        assert!(matches!(global(&interpreter, "mixed"), Some(Value::Number(n)) if n == 7.5));
+        assert_eq!(
+            global(&interpreter, "one"),
+            Some(Value::String("one".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_power_and_bitwise() {
+        let interpreter = interpret(
+            r#"
+            let square = -3 ** 2;
+            let tower = 2 ** 3 ** 2;
+            let half = 2 ** -1;
+            let cents = 1.1d ** 2;
+            let flags = 6 & 3 | 8 ^ 1;
+            let masked = 12 & 4 == 4;
+            let shifted = 1 + 1 << 4 >> 1;
+            let whole = 6.0 & 3;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "square"), "-9");
+        assert_eq!(global_text(&interpreter, "tower"), "512");
+        assert_eq!(global_text(&interpreter, "half"), "0.5");
+        assert_eq!(global_text(&interpreter, "cents"), "1.21");
+        assert_eq!(global_text(&interpreter, "flags"), "11");
+        assert_eq!(global_text(&interpreter, "masked"), "true");
+        assert_eq!(global_text(&interpreter, "shifted"), "16");
+        assert_eq!(global_text(&interpreter, "whole"), "2");
+    }
+
+    #[test]
+    fn test_bitwise_errors() {
+        let errors: Vec<String> = ["1.5 | 1;", "1 << -1;", "1 << 63;", "2 ** 64;"]
+            .iter()
+            .map(|source| expression_error("", source).msg)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Bitwise | needs whole numbers, a Number or Decimal with a fractional part can't be used",
+                "Can only shift whole numbers, by a non-negative amount",
+                "Integer overflow.",
+                "Integer overflow."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let interpreter = interpret(
+            r#"
+            let total = 0;
+            for (let i = 1; i <= 4; i += 1) {
+                total += i;
+            }
+            total *= 3;
+            total -= 6;
+            total /= 4;
+            let rest = 17;
+            rest %= 5;
+            let label = "a";
+            label += "b";
+
+            let calls = 0;
+            gorilla position() {
+                calls += 1;
+                return 1;
+            }
+            let counts = [10, 20];
+            counts[position()] += 5;
+            let tally = {"x": 1};
+            tally["x"] *= 7;
+
+            Jungle Account {
+                init() { this.balance = 100; }
+            }
+            let account = Account();
+            account.balance -= 30;
+            let balance = account.balance;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "total"), "6.0");
+        assert_eq!(global_text(&interpreter, "rest"), "2");
+        assert_eq!(global_text(&interpreter, "label"), "ab");
+        assert_eq!(global_text(&interpreter, "calls"), "1");
+        assert_eq!(global_text(&interpreter, "counts"), "[10, 25]");
+        assert_eq!(global_text(&interpreter, "tally"), r#"{"x": 7}"#);
+        assert_eq!(global_text(&interpreter, "balance"), "70");
+    }
+
+    #[test]
+    fn test_increment_and_decrement() {
+        let interpreter = interpret(
+            r#"
+            let x = 5;
+            let before = x++;
+            let after = ++x;
+            let down = x--;
+            --x;
+            let steps = 0;
+            for (let i = 0; i < 3; i++) steps++;
+            let counts = [1, 2];
+            let old = counts[1]++;
+            let tally = {"n": 0.5};
+            ++tally["n"];
+            Jungle Counter { init() { this.count = 0; } }
+            let counter = Counter();
+            counter.count++;
+            let count = counter.count--;
+            let left = counter.count;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "before"), "5");
+        assert_eq!(global_text(&interpreter, "after"), "7");
+        assert_eq!(global_text(&interpreter, "down"), "7");
+        assert_eq!(global_text(&interpreter, "x"), "5");
+        assert_eq!(global_text(&interpreter, "steps"), "3");
+        assert_eq!(global_text(&interpreter, "old"), "2");
+        assert_eq!(global_text(&interpreter, "counts"), "[1, 3]");
+        assert_eq!(global_text(&interpreter, "tally"), r#"{"n": 1.5}"#);
+        assert_eq!(global_text(&interpreter, "count"), "1");
+        assert_eq!(global_text(&interpreter, "left"), "0");
+
+        assert_eq!(
+            expression_error("let s = \"a\";", "s++;").msg,
+            "Can only add literals of same type. Supported types: Int, Number, Decimal, String"
+        );
+    }
+
+    #[test]
+    fn test_conditional_and_coalesce() {
+        let interpreter = interpret(
+            r#"
+            let config = {"retries": 0, "name": null};
+            let retries = config["retries"] ?? 3;
+            let name = config["name"] ?? config["fallback"] ?? "goryl";
+            let size = retries > 0 ? "some" : retries == 0 ? "none" : "negative";
+            let calls = 0;
+            gorilla touch() {
+                calls += 1;
+                return 1;
+            }
+            let skipped = true ? "yes" : touch();
+            let kept = 5 ?? touch();
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "retries"), "0");
+        assert_eq!(global_text(&interpreter, "name"), "goryl");
+        assert_eq!(global_text(&interpreter, "size"), "none");
+        assert_eq!(global_text(&interpreter, "skipped"), "yes");
+        assert_eq!(global_text(&interpreter, "kept"), "5");
+        assert_eq!(global_text(&interpreter, "calls"), "0");
+    }
+
+    #[test]
+    fn test_lambdas() {
+        let interpreter = interpret(
+            r#"
+            gorilla apply(f, x) { return f(x); }
+            let doubled = apply(gorilla (x) { return x * 2; }, 21);
+            let offset = 10;
+            let shift = (x) => x + offset;
+            offset = 20;
+            let shifted = shift(1);
+            let pair = (a, b) => { return [b, a]; };
+            let swapped = pair(1, 2);
+            let answer = () => 42;
+            let called = answer();
+            let grouped = (offset) * 2;
+            gorilla counter() {
+                let count = 0;
+                return () => count += 1;
+            }
+            let next = counter();
+            next();
+            let second = next();
+            let shown = "${answer}";
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "doubled"), "42");
+        assert_eq!(global_text(&interpreter, "shifted"), "21");
+        assert_eq!(global_text(&interpreter, "swapped"), "[2, 1]");
+        assert_eq!(global_text(&interpreter, "called"), "42");
+        assert_eq!(global_text(&interpreter, "grouped"), "40");
+        assert_eq!(global_text(&interpreter, "second"), "2");
+        assert_eq!(global_text(&interpreter, "shown"), "<gorilla anonymous>");
+    }
+
+    #[test]
+    fn test_decimals() {
+        let interpreter = interpret(
+            r#"
+            let amount = 100d;
+            let discount = 0.2d;
+            let total = amount - amount * discount;
+            let tenth = 0.1d + 0.2d == 0.3d;
+            let inexact = 0.1 + 0.2 == 0.3;
+            let price = 19.99d * 3;
+            let share = 10d / 3;
+            let cheaper = 19.99d < 20;
+            let prices = {0.5d: "half"};
+            let half = prices[0.5];
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "total"), "80.0");
+        assert_eq!(global_text(&interpreter, "tenth"), "true");
+        // the same sum with Numbers picks up binary rounding error.
+        assert_eq!(global_text(&interpreter, "inexact"), "false");
+        assert_eq!(global_text(&interpreter, "price"), "59.97");
+        assert_eq!(global_text(&interpreter, "share"), "3.33333333333333333333");
+        assert_eq!(global_text(&interpreter, "cheaper"), "true");
+        assert_eq!(global_text(&interpreter, "half"), "half");
+        // too many digits to work out is an overflow, found before any multiplying starts.
+        let error = expression_error("", "9999999999d ** 5000;");
+        assert_eq!(error.kind.to_string(), "OverflowError");
+    }
+
+    #[test]
+    fn test_decimal_context() {
+        // the context comes from the command line, as it does for a script that is run.
+        let args = ["--scale", "2", "--rounding", "down"].map(String::from);
+        let options = super::run::parse_options(&args).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_decimal_context(options.decimal_context);
+        interpreter.interpret_statements(&parse("let third = 2d / 3;"));
+        assert_eq!(global_text(&interpreter, "third"), "0.66");
+        assert_eq!(
+            expression_error("", "1d + 0.5;").msg,
+            "Can only add literals of same type. Supported types: Int, Number, Decimal, String"
+        );
+    }
+
+    #[test]
+    fn test_integer_errors() {
+        let errors: Vec<String> = [
+            "9223372036854775807 + 1;",
+            "-0x7FFFFFFFFFFFFFFF - 2;",
+            "1 ~/ 0;",
+            "5 % 0;",
+        ]
+        .iter()
+        .map(|source| expression_error("", source).msg)
+        .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Integer overflow.",
+                "Integer overflow.",
+                "Division by zero.",
+                "Division by zero."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arity_error() {
+        let setup = "gorilla add(a, b = 1, ...more) { return a; }\nJungle Point { init(x, y) {} }";
+        let errors: Vec<String> = [
+            "add(b: 2);",
+            "add(1, c: 2);",
+            "add(1, a: 2);",
+            "Point(1, 2, 3);",
+        ]
+        .iter()
+        .map(|source| expression_error(setup, source).to_string())
+        .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "Missing argument 'a', expected add(a, b?, ...more) but got add(b: Int) on line 1",
+                "Unknown argument 'c', expected add(a, b?, ...more) but got add(Int, c: Int) on line 1",
+                "Argument 'a' given twice, expected add(a, b?, ...more) but got add(Int, a: Int) on line 1",
+                "Too many arguments, expected Point(x, y) but got Point(Int, Int, Int) on line 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let interpreter = interpret(
+            r#"
+            gorilla report(title, width = 80, border = width / 2, ...rows) {
+                return "${title} ${width} ${border} ${rows}";
+            }
+            let plain = report("a");
+            let named = report(border: "-", title: "b");
+            let extra = report("c", 10, 1, "x", "y");
+            let sum = (...xs) => xs;
+            let all = sum(1, 2, 3);
+            let base = 10;
+            gorilla later(n = base) { return n; }
+            base = 20;
+            let current = later();
+            Jungle Point {
+                init(x = 0, y = 0) { this.x = x; this.y = y; }
+            }
+            let point = Point(y: 5);
+            let y = point.y;
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "plain"), "a 80 40.0 []");
+        assert_eq!(global_text(&interpreter, "named"), "b 80 - []");
+        assert_eq!(global_text(&interpreter, "extra"), r#"c 10 1 ["x", "y"]"#);
+        assert_eq!(global_text(&interpreter, "all"), "[1, 2, 3]");
+        assert_eq!(global_text(&interpreter, "current"), "20");
+        assert_eq!(global_text(&interpreter, "y"), "5");
+    }
+
+    #[test]
+    fn test_assign_undefined() {
+        let mut scanner = Scanner::new("\nmissing = 1;".to_string());
+        scanner.scan_tokens();
+        let mut statements = Parser::new(scanner.tokens).parse();
+        let mut interpreter = Interpreter::new();
+        match statements.remove(0) {
+            Stmt::Expression(e) => {
+                let error = interpreter.interpret_expression(&e.expression).unwrap_err();
+                assert_eq!(error.to_string(), "Undefined variable 'missing' on line 2");
+            }
+            _ => panic!("expected an expression statement"),
+        }
+        assert!(global(&interpreter, "missing").is_none());
+    }
+
+    #[test]
+    fn test_try_catch_finally() {
+        let interpreter = interpret(
+            r#"
+            let message; let line; let kind; let steps = "";
+            try {
+                steps = steps + "a";
+                1 ~/ 0;
+                steps = steps + "b";
+            } catch (e) {
+                message = e.message; line = e.line; kind = e.kind;
+            } finally {
+                steps = steps + "f";
+            }
+            gorilla fail(n) { if (n == 0) throw "deep"; return fail(n - 1); }
+            let thrown;
+            try { fail(3); } catch (e) { thrown = "${e.kind} ${e.message} ${e.value} ${e.line}"; }
+            Jungle Oops { init(message) { this.message = message; } }
+            let custom;
+            try { throw Oops("bad"); } catch (e) { custom = e.message; }
+            gorilla early() {
+                try { return "try"; } finally { steps = steps + "!"; }
+            }
+            let returned = early();
+            gorilla overridden() {
+                try { throw 1; } finally { return "finally"; }
+            }
+            let replaced = overridden();
+            let count = 0;
+            while (count < 5) {
+                count = count + 1;
+                try { if (count == 2) break; } catch { count = 100; }
+            }
+            let rethrown;
+            try {
+                try { missing; } catch (e) { throw e; }
+            } catch (e) { rethrown = e.kind; }
+            "#,
+        );
+        assert_eq!(global_text(&interpreter, "message"), "Division by zero.");
+        assert_eq!(global(&interpreter, "line"), Some(Value::Int(5)));
+        assert_eq!(global_text(&interpreter, "kind"), "DivisionByZeroError");
+        assert_eq!(global_text(&interpreter, "steps"), "af!");
+        assert_eq!(global_text(&interpreter, "thrown"), "Error deep deep 12");
+        assert_eq!(global_text(&interpreter, "custom"), "bad");
+        assert_eq!(global_text(&interpreter, "returned"), "try");
+        assert_eq!(global_text(&interpreter, "replaced"), "finally");
+        assert_eq!(global(&interpreter, "count"), Some(Value::Int(2)));
+        assert_eq!(global_text(&interpreter, "rethrown"), "NameError");
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let broken = std::env::temp_dir().join("goryl-broken-import.grl");
+        std::fs::write(&broken, "let a = 1;\nlet b = ;").unwrap();
+        let interpreter = interpret(&format!(
+            r#"
+            let kind; let missing; let unparsable;
+            try {{ import "goryl-missing-import.grl"; }} catch (e) {{ kind = e.kind; missing = e.message; }}
+            try {{ import "{}"; }} catch (e) {{ unparsable = e.message; }}
+            "#,
+            broken.display()
+        ));
+        assert_eq!(global_text(&interpreter, "kind"), "ImportError");
+        assert!(global_text(&interpreter, "missing")
+            .starts_with(r#"Could not import "goryl-missing-import.grl": "#));
+        assert_eq!(
+            global_text(&interpreter, "unparsable"),
+            format!(
+                r#"Could not import "{}": Unexpected token on line 2"#,
+                broken.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_import_cycles() {
+        let dir = std::env::temp_dir();
+        let own = dir.join("goryl-self-import.grl");
+        std::fs::write(&own, format!(r#"import "{}";"#, own.display())).unwrap();
+        let first = dir.join("goryl-cycle-first.grl");
+        let second = dir.join("goryl-cycle-second.grl");
+        std::fs::write(&first, format!(r#"import "{}";"#, second.display())).unwrap();
+        std::fs::write(&second, format!(r#"import "{}";"#, first.display())).unwrap();
+        let interpreter = interpret(&format!(
+            r#"
+            let own; let cycle;
+            try {{ import "{}"; }} catch (e) {{ own = e.message; }}
+            try {{ import "{}"; }} catch (e) {{ cycle = e.message; }}
+            "#,
+            own.display(),
+            first.display()
+        ));
+        let cycle = |file: &std::path::Path| {
+            format!(
+                r#"Could not import "{}": it is already being imported"#,
+                file.display()
+            )
+        };
+        assert_eq!(global_text(&interpreter, "own"), cycle(&own));
+        assert_eq!(global_text(&interpreter, "cycle"), cycle(&first));
+    }
+
+    #[test]
+    fn test_uncaught_error() {
+        let statements = parse(
+            "let before = 1;\ngorilla inner() { return 1 ~/ 0; }\ngorilla outer() { return inner(); }\nouter();\nlet after = 2;",
+        );
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.execute_statements(&statements).unwrap_err();
+        assert_eq!(error.to_string(), "Division by zero. on line 2");
+        assert_eq!((error.span.column, error.span.length), (28, 2));
+        assert_eq!(
+            error.trace,
+            vec!["inner() called on line 3", "outer() called on line 4"]
+        );
+        // the script stops at the error.
+        interpreter.interpret_statements(&statements);
+        assert!(global(&interpreter, "before").is_some());
+        assert!(global(&interpreter, "after").is_none());
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        // the limit is sized for the stack that scripts are run with, not the test thread's.
+        let script = std::thread::Builder::new()
+            .stack_size(super::run::STACK_SIZE)
+            .spawn(|| {
+                let interpreter = interpret(&format!(
+                    r#"
+                    gorilla down(n) {{ if (n == 0) return 0; return 1 + down(n - 1); }}
+                    let deep = down(900);
+                    let kind;
+                    try {{ down(5000); }} catch (e) {{ kind = e.kind; }}
+                    let after = down(10);
+                    gorilla nested(n) {{
+                        if (n == 0) return 0;
+                        return ((((((((((((((((((((nested(n - 1)))))))))))))))))))));
+                    }}
+                    let stack;
+                    try {{ nested(5000); }} catch (e) {{ stack = e.message; }}
+                    let chain;
+                    try {{ chain = 0{}; }} catch (e) {{ chain = e.message; }}
+                    "#,
+                    " + 1".repeat(20000)
+                ));
+                ["deep", "kind", "after", "stack", "chain"]
+                    .map(|name| global_text(&interpreter, name))
+            })
+            .unwrap();
+        let too_deep = "Maximum evaluation depth of 10000 exceeded";
+        assert_eq!(
+            script.join().unwrap(),
+            ["900", "RecursionError", "10", too_deep, too_deep]
+        );
     }
 }
@@ -1,17 +1,72 @@
+use super::decimal::{DecimalContext, Rounding, MAX_DIGITS};
 use super::Interpreter;
 use crate::ast::parser::Parser;
-use crate::errors::runtime_error::RuntimeError;
+use crate::ast::resolver::Resolver;
 use crate::errors::Error;
 use crate::syntax::scanner::Scanner;
 use std::io;
 use std::io::Write;
+use std::thread;
 
 use super::value::Value;
 
-pub fn run_file(file: String) {
+// the parser, resolver and interpreter recurse on the native stack. Nesting is capped by MAX_NESTING
+// and the interpreter stops with an error once it runs too deeply, this is enough stack for both.
+pub(crate) const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// what the command line asks for: [--scale digits] [--rounding mode] [file].
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub decimal_context: DecimalContext, // how scripts divide Decimals.
+    pub file: Option<String>,            // the prompt is started without one.
+}
+
+pub fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        decimal_context: DecimalContext::default(),
+        file: None,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--scale" => {
+                options.decimal_context.scale = args
+                    .next()
+                    .and_then(|digits| digits.parse().ok())
+                    .filter(|digits| *digits as usize <= MAX_DIGITS)
+                    .ok_or(format!(
+                        "--scale takes a number of digits up to {}",
+                        MAX_DIGITS
+                    ))?;
+            }
+            "--rounding" => {
+                options.decimal_context.rounding = args
+                    .next()
+                    .and_then(|mode| Rounding::from_name(mode))
+                    .ok_or("--rounding takes half-even, half-up, down, up, floor or ceiling")?;
+            }
+            _ if options.file.is_none() => options.file = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+// runs on its own thread for the larger stack. A panic only ends the script, but overflowing the
+// stack would still abort the whole process, which is what the limits above are for.
+pub fn run_file(file: String, context: DecimalContext) {
+    let script = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || run_source(file, context));
+    if !matches!(script.map(|handle| handle.join()), Ok(Ok(()))) {
+        println!("Bug: The interpreter stopped unexpectedly");
+    }
+}
+
+fn run_source(file: String, context: DecimalContext) {
     let mut scanner = Scanner::new(file);
     scanner.scan_tokens();
-    if scanner.errors.len() > 0 {
+    if !scanner.errors.is_empty() {
         for error in scanner.errors.iter() {
             error.report();
         }
@@ -20,34 +75,116 @@ pub fn run_file(file: String) {
     let tokens = scanner.tokens;
     let mut parser = Parser::new(tokens);
     let ast = parser.parse();
+    if !parser.errors.is_empty() {
+        for error in parser.errors.iter() {
+            error.report();
+        }
+        return;
+    }
+    let mut resolver = Resolver::new();
+    resolver.resolve(&ast);
+    if !resolver.errors.is_empty() {
+        for error in resolver.errors.iter() {
+            error.report();
+        }
+        return;
+    }
     let mut interpreter = Interpreter::new();
-    interpreter.interpret_statements(ast);
+    interpreter.set_decimal_context(context);
+    interpreter.interpret_statements(&ast);
 }
 
-pub fn run_line(line: String) {
-    run_file(line);
+pub fn run_line(line: String, context: DecimalContext) {
+    run_file(line, context);
 }
 
-pub fn run_prompt() {
+pub fn run_prompt(context: DecimalContext) {
     loop {
         let mut line = String::new();
         print!("> ");
-        io::stdout().flush().unwrap();
-        io::stdin()
-            .read_line(&mut line)
-            .expect("Could not read line");
-        run_line(line);
-    }
-}
-
-pub fn print_value(val: Result<Value, RuntimeError>) {
-    match val {
-        Ok(val) => match val {
-            Value::String(s) => println!("{:?}", s),
-            Value::Number(n) => println!("{:?}", n),
-            Value::Bool(b) => println!("{:?}", b),
-            Value::None => println!("Null"),
-        },
-        Err(e) => e.report(),
+        // a failed flush only delays the prompt.
+        let _ = io::stdout().flush();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return, // end of input.
+            Ok(_) => run_line(line, context),
+        }
+    }
+}
+
+// strings print as their text, without quotes and with escapes already turned into the characters.
+pub fn print_value(out: &mut impl Write, val: Value) {
+    // like the prompt's flush, output that can't be written is not worth stopping the script for.
+    let _ = writeln!(out, "{}", val);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_options, print_value, Options};
+    use crate::interpreter::decimal::{DecimalContext, Rounding};
+    use crate::interpreter::value::Value;
+    use crate::syntax::scanner::Scanner;
+    use crate::syntax::token::Literal;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn options(command: &str) -> Result<Options, String> {
+        let args: Vec<String> = command.split_whitespace().map(String::from).collect();
+        parse_options(&args)
+    }
+
+    #[test]
+    fn test_options() {
+        assert_eq!(
+            options("--scale 2 --rounding half-up prices.grl"),
+            Ok(Options {
+                decimal_context: DecimalContext {
+                    scale: 2,
+                    rounding: Rounding::HalfUp,
+                },
+                file: Some("prices.grl".to_string()),
+            })
+        );
+        assert_eq!(
+            options(""),
+            Ok(Options {
+                decimal_context: DecimalContext::default(),
+                file: None,
+            })
+        );
+        let errors: Vec<String> = [
+            "--scale",
+            "--scale 99999",
+            "--rounding sideways",
+            "a.grl b.grl",
+        ]
+        .iter()
+        .map(|command| options(command).unwrap_err())
+        .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "--scale takes a number of digits up to 10000",
+                "--scale takes a number of digits up to 10000",
+                "--rounding takes half-even, half-up, down, up, floor or ceiling",
+                "Unexpected argument b.grl",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_value() {
+        let mut scanner = Scanner::new(r#""tab\there, \"quoted\" \u{1F98D}""#.to_string());
+        scanner.scan_tokens();
+        let Literal::String(text) = scanner.tokens[0].literal.clone() else {
+            panic!("expected a string literal");
+        };
+        let mut out = Vec::new();
+        print_value(&mut out, Value::String(text));
+        let list = vec![Value::String("a".to_string()), Value::Int(1)];
+        print_value(&mut out, Value::List(Rc::new(RefCell::new(list))));
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "tab\there, \"quoted\" \u{1F98D}\n[\"a\", 1]\n"
+        );
     }
 }
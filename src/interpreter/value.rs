@@ -1,24 +1,370 @@
+use super::class::{Class, Instance};
+use super::decimal::{Decimal, DecimalContext};
+use super::function::Function;
+use super::map::Map;
+use std::cell::RefCell;
 use std::cmp::{PartialEq, PartialOrd};
-use std::ops::{Add, Div, Mul, Neg, Not, Sub};
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub enum Value {
     String(String),
+    Int(i64),
     Number(f64),
+    Decimal(Decimal),
     Bool(bool),
     None,
+    Function(Rc<Function>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    List(Rc<RefCell<Vec<Value>>>), // shared so every alias of a list sees updates made through the others.
+    Map(Rc<RefCell<Map>>),
+}
+
+impl Value {
+    // null, false, 0, "", [] and {} are falsy, everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::String(s) => !s.is_empty(),
+            Value::List(elements) => !elements.borrow().is_empty(),
+            Value::Map(map) => !map.borrow().is_empty(),
+            Value::Int(n) => *n != 0,
+            Value::Number(n) => *n != 0.0,
+            Value::Decimal(d) => !d.is_zero(),
+            Value::Bool(b) => *b,
+            Value::None => false,
+            Value::Function(_) | Value::Class(_) | Value::Instance(_) => true,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "String",
+            Value::Int(_) => "Int",
+            Value::Number(_) => "Number",
+            Value::Decimal(_) => "Decimal",
+            Value::Bool(_) => "Bool",
+            Value::None => "Null",
+            Value::Function(_) => "Function",
+            Value::Class(_) => "Jungle",
+            Value::Instance(_) => "Instance",
+            Value::List(_) => "List",
+            Value::Map(_) => "Map",
+        }
+    }
+
+    // how a value is shown inside a collection, strings are quoted there.
+    pub fn repr(&self) -> String {
+        match self {
+            Value::String(s) => format!("{:?}", s),
+            _ => self.to_string(),
+        }
+    }
+}
+
+// strings are shown raw at the top level but quoted inside collections, so ["a"] and ["a", "b"] stay readable.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, &mut Vec::new())
+    }
+}
+
+impl Value {
+    // open holds the lists and maps being printed around this value, one that contains itself is shown as [...] or {...}.
+    fn write(&self, f: &mut fmt::Formatter, open: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Number(n) => write!(f, "{:?}", n),
+            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::None => write!(f, "Null"),
+            Value::Function(function) => write!(f, "{}", function),
+            Value::Class(class) => write!(f, "{}", class),
+            Value::Instance(instance) => write!(f, "{}", instance.borrow()),
+            Value::List(elements) => {
+                let list = Rc::as_ptr(elements) as *const ();
+                if open.contains(&list) {
+                    return write!(f, "[...]");
+                }
+                open.push(list);
+                write!(f, "[")?;
+                for (i, element) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.write_element(f, open)?;
+                }
+                open.pop();
+                write!(f, "]")
+            }
+            Value::Map(entries) => {
+                let map = Rc::as_ptr(entries) as *const ();
+                if open.contains(&map) {
+                    return write!(f, "{{...}}");
+                }
+                open.push(map);
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.to_value().write_element(f, open)?;
+                    write!(f, ": ")?;
+                    value.write_element(f, open)?;
+                }
+                open.pop();
+                write!(f, "}}")
+            }
+        }
+    }
+
+    // like repr, for a value printed inside a list or map.
+    fn write_element(&self, f: &mut fmt::Formatter, open: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            _ => self.write(f, open),
+        }
+    }
+
+    // pairs holds the lists and maps being compared around these two, meeting the same pair again means
+    // every element compared so far matched, so the cycle is treated as equal.
+    fn equals(&self, other: &Self, pairs: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Value::List(l1), Value::List(l2)) => {
+                let pair = (Rc::as_ptr(l1) as *const (), Rc::as_ptr(l2) as *const ());
+                if Rc::ptr_eq(l1, l2) || pairs.contains(&pair) {
+                    return true;
+                }
+                let (l1, l2) = (l1.borrow(), l2.borrow());
+                if l1.len() != l2.len() {
+                    return false;
+                }
+                pairs.push(pair);
+                let equal = l1
+                    .iter()
+                    .zip(l2.iter())
+                    .all(|(v1, v2)| v1.equals(v2, pairs));
+                pairs.pop();
+                equal
+            }
+            // two maps are equal when they hold the same entries, whatever order they were added in.
+            (Value::Map(m1), Value::Map(m2)) => {
+                let pair = (Rc::as_ptr(m1) as *const (), Rc::as_ptr(m2) as *const ());
+                if Rc::ptr_eq(m1, m2) || pairs.contains(&pair) {
+                    return true;
+                }
+                let (m1, m2) = (m1.borrow(), m2.borrow());
+                if m1.len() != m2.len() {
+                    return false;
+                }
+                pairs.push(pair);
+                let equal = m1.iter().all(|(key, v1)| match m2.get(key) {
+                    Some(v2) => v1.equals(v2, pairs),
+                    None => false,
+                });
+                pairs.pop();
+                equal
+            }
+            _ => self == other,
+        }
+    }
+}
+
+// why an operator could not produce a value, the interpreter turns each of these into a runtime error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpError {
+    Type,
+    Overflow,
+    DivisionByZero,
+}
+
+impl Value {
+    // ~/ always gives an Int, the quotient is truncated towards zero.
+    pub fn int_div(self, rhs: Self) -> Result<Self, OpError> {
+        if self.is_number() && rhs.is_zero_number() {
+            return Err(OpError::DivisionByZero);
+        }
+        match (self, rhs) {
+            (Value::Int(n1), Value::Int(n2)) => {
+                n1.checked_div(n2).map(Value::Int).ok_or(OpError::Overflow)
+            }
+            (lhs, rhs) if matches!(lhs, Value::Decimal(_)) || matches!(rhs, Value::Decimal(_)) => {
+                match (lhs.as_decimal(), rhs.as_decimal()) {
+                    (Some(d1), Some(d2)) => d1
+                        .div_rem(&d2)
+                        .0
+                        .to_i64()
+                        .map(Value::Int)
+                        .ok_or(OpError::Overflow),
+                    _ => Err(OpError::Type),
+                }
+            }
+            (lhs, rhs) => match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(n1), Some(n2)) => {
+                    let quotient = (n1 / n2).trunc();
+                    // i64::MIN is a power of two, so it and its negation are exact as f64.
+                    if quotient >= i64::MIN as f64 && quotient < -(i64::MIN as f64) {
+                        Ok(Value::Int(quotient as i64))
+                    } else {
+                        Err(OpError::Overflow)
+                    }
+                }
+                _ => Err(OpError::Type),
+            },
+        }
+    }
+
+    // Decimals divide at the context's scale, the / operator uses the default context.
+    pub fn divide(self, rhs: Self, context: DecimalContext) -> Result<Self, OpError> {
+        if self.is_number() && rhs.is_zero_number() {
+            return Err(OpError::DivisionByZero);
+        }
+        if matches!(self, Value::Decimal(_)) || matches!(rhs, Value::Decimal(_)) {
+            return match (self.as_decimal(), rhs.as_decimal()) {
+                (Some(d1), Some(d2)) => d1
+                    .div(&d2, context)
+                    .map(Value::Decimal)
+                    .ok_or(OpError::DivisionByZero),
+                _ => Err(OpError::Type),
+            };
+        }
+        match (self.as_f64(), rhs.as_f64()) {
+            (Some(n1), Some(n2)) => Ok(Value::Number(n1 / n2)),
+            _ => Err(OpError::Type),
+        }
+    }
+
+    // an Int result for Int bases with a non-negative Int exponent, a negative exponent gives a Number like 2 ** -1 = 0.5.
+    // Decimal bases need a whole exponent and divide at the context's scale when it is negative.
+    pub fn power(self, rhs: Self, context: DecimalContext) -> Result<Self, OpError> {
+        let negative_exponent = rhs
+            .as_exact_decimal()
+            .is_some_and(|d| d < Decimal::from_i64(0));
+        if self.is_zero_number() && negative_exponent {
+            return Err(OpError::DivisionByZero);
+        }
+        match (self, rhs) {
+            (Value::Int(base), Value::Int(exponent)) if exponent >= 0 => u32::try_from(exponent)
+                .ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .map(Value::Int)
+                .ok_or(OpError::Overflow),
+            (lhs, rhs) if matches!(lhs, Value::Decimal(_)) || matches!(rhs, Value::Decimal(_)) => {
+                let (base, exponent) = match (lhs.as_decimal(), rhs.as_integer()) {
+                    (Some(base), Some(exponent)) => (base, exponent),
+                    _ => return Err(OpError::Type),
+                };
+                let result = u32::try_from(exponent.unsigned_abs())
+                    .ok()
+                    .and_then(|exponent| base.powi(exponent))
+                    .ok_or(OpError::Overflow)?;
+                match exponent < 0 {
+                    true => Decimal::from_i64(1)
+                        .div(&result, context)
+                        .map(Value::Decimal)
+                        .ok_or(OpError::DivisionByZero),
+                    false => Ok(Value::Decimal(result)),
+                }
+            }
+            (lhs, rhs) => match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(base), Some(exponent)) => Ok(Value::Number(base.powf(exponent))),
+                _ => Err(OpError::Type),
+            },
+        }
+    }
+
+    // whole numbers of any numeric type, for the bitwise operators.
+    fn as_integer(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            Value::Number(n)
+                if n.fract() == 0.0 && *n >= i64::MIN as f64 && *n < -(i64::MIN as f64) =>
+            {
+                Some(*n as i64)
+            }
+            Value::Decimal(d) => d.to_i64(),
+            _ => None,
+        }
+    }
+
+    // Ints convert exactly, Numbers are left out so an f64 can't quietly leak rounding errors into a Decimal.
+    fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Int(n) => Some(Decimal::from_i64(*n)),
+            Value::Decimal(d) => Some(d.clone()),
+            _ => None,
+        }
+    }
+
+    // for comparisons, where a Number is compared by its exact value.
+    fn as_exact_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Number(n) => Decimal::from_f64(*n),
+            _ => self.as_decimal(),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(self, Value::Int(_) | Value::Number(_) | Value::Decimal(_))
+    }
+
+    fn is_zero_number(&self) -> bool {
+        match self {
+            Value::Int(n) => *n == 0,
+            Value::Number(n) => *n == 0.0,
+            Value::Decimal(d) => d.is_zero(),
+            _ => false,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(*n as f64),
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+// two Ints stay an Int and fail on overflow instead of wrapping, mixing in a Number promotes the result to a Number.
+// a Decimal mixes with Ints and stays exact, mixing it with a Number is a type error.
+fn arithmetic(
+    lhs: Value,
+    rhs: Value,
+    int_op: fn(i64, i64) -> Option<i64>,
+    number_op: fn(f64, f64) -> f64,
+    decimal_op: fn(&Decimal, &Decimal) -> Decimal,
+) -> Result<Value, OpError> {
+    match (lhs, rhs) {
+        (Value::Int(n1), Value::Int(n2)) => int_op(n1, n2).map(Value::Int).ok_or(OpError::Overflow),
+        (Value::Decimal(d1), rhs) => match rhs.as_decimal() {
+            Some(d2) => Ok(Value::Decimal(decimal_op(&d1, &d2))),
+            None => Err(OpError::Type),
+        },
+        (lhs, Value::Decimal(d2)) => match lhs.as_decimal() {
+            Some(d1) => Ok(Value::Decimal(decimal_op(&d1, &d2))),
+            None => Err(OpError::Type),
+        },
+        (lhs, rhs) => match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(n1), Some(n2)) => Ok(Value::Number(number_op(n1, n2))),
+            _ => Err(OpError::Type),
+        },
+    }
 }
 
 // unary operators:
 
 impl Neg for Value {
-    type Output = Option<Self>;
+    type Output = Result<Self, OpError>;
     fn neg(self) -> Self::Output {
         match self {
-            Value::String(_) => None,
-            Value::Number(n) => Some(Value::Number(-n)),
-            Value::Bool(_) => None,
-            Value::None => None,
+            Value::Int(n) => n.checked_neg().map(Value::Int).ok_or(OpError::Overflow),
+            Value::Number(n) => Ok(Value::Number(-n)),
+            Value::Decimal(d) => Ok(Value::Decimal(d.neg())),
+            _ => Err(OpError::Type),
         }
     }
 }
@@ -26,15 +372,7 @@ impl Neg for Value {
 impl Not for Value {
     type Output = Self;
     fn not(self) -> Self::Output {
-        match self {
-            Value::String(s) => Value::Bool(!s.is_empty()),
-            Value::Number(n) => match n {
-                0.0 => Value::Bool(true), // matching an f64 will be a hard error in the future, look into this
-                _ => Value::Bool(false),
-            },
-            Value::Bool(bool) => Value::Bool(!bool),
-            Value::None => Value::Bool(true),
-        }
+        Value::Bool(!self.is_truthy())
     }
 }
 
@@ -42,48 +380,109 @@ impl Not for Value {
 // arithmetic:
 
 impl Add for Value {
-    type Output = Option<Self>;
+    type Output = Result<Self, OpError>;
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Value::String(s1), Value::String(s2)) => Some(Value::String(s1 + &s2)), // In Rust, the + operator for strings works by taking ownership of the left-hand side (s1 in this case) and borrowing the right-hand side (s2). This is why you need to borrow s2 using &s2, but you don't need to do this for s1.
-            (Value::Number(n1), Value::Number(n2)) => Some(Value::Number(n1 + n2)),
-            _ => None,
+            (Value::String(s1), Value::String(s2)) => Ok(Value::String(s1 + &s2)), // In Rust, the + operator for strings works by taking ownership of the left-hand side (s1 in this case) and borrowing the right-hand side (s2). This is why you need to borrow s2 using &s2, but you don't need to do this for s1.
+            (lhs, rhs) => arithmetic(lhs, rhs, i64::checked_add, |n1, n2| n1 + n2, Decimal::add),
         }
     }
 }
 
 impl Sub for Value {
-    type Output = Option<Self>;
+    type Output = Result<Self, OpError>;
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Some(Value::Number(n1 - n2)), // We cannot do strings because think about when they are disjoint.
-            _ => None,
-        }
+        // We cannot do strings because think about when they are disjoint.
+        arithmetic(self, rhs, i64::checked_sub, |n1, n2| n1 - n2, Decimal::sub)
     }
 }
 
 impl Mul for Value {
-    type Output = Option<Self>;
+    type Output = Result<Self, OpError>;
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => Some(Value::Number(n1 * n2)),
-            _ => None,
-        }
+        arithmetic(self, rhs, i64::checked_mul, |n1, n2| n1 * n2, Decimal::mul)
     }
 }
 
+// / is true division, so 7 / 2 is 3.5 even though both sides are Ints.
 impl Div for Value {
-    type Output = Option<Self>;
+    type Output = Result<Self, OpError>;
     fn div(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Value::Number(n1), Value::Number(n2)) => {
-                if n2 == 0.0 {
-                    return None;
-                } else {
-                    return Some(Value::Number(n1 / n2));
-                }
+        self.divide(rhs, DecimalContext::default())
+    }
+}
+
+// the remainder takes the sign of the left side, matching ~/ truncating towards zero.
+impl Rem for Value {
+    type Output = Result<Self, OpError>;
+    fn rem(self, rhs: Self) -> Self::Output {
+        if self.is_number() && rhs.is_zero_number() {
+            return Err(OpError::DivisionByZero);
+        }
+        arithmetic(
+            self,
+            rhs,
+            i64::checked_rem,
+            |n1, n2| n1 % n2,
+            |d1, d2| d1.div_rem(d2).1,
+        )
+    }
+}
+
+// bitwise, on whole numbers only:
+
+fn bitwise(lhs: Value, rhs: Value, op: fn(i64, i64) -> i64) -> Result<Value, OpError> {
+    match (lhs.as_integer(), rhs.as_integer()) {
+        (Some(n1), Some(n2)) => Ok(Value::Int(op(n1, n2))),
+        _ => Err(OpError::Type),
+    }
+}
+
+impl BitAnd for Value {
+    type Output = Result<Self, OpError>;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        bitwise(self, rhs, |n1, n2| n1 & n2)
+    }
+}
+
+impl BitOr for Value {
+    type Output = Result<Self, OpError>;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        bitwise(self, rhs, |n1, n2| n1 | n2)
+    }
+}
+
+impl BitXor for Value {
+    type Output = Result<Self, OpError>;
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        bitwise(self, rhs, |n1, n2| n1 ^ n2)
+    }
+}
+
+// shifting out any set bit is an overflow, a negative shift amount is a type error.
+impl Shl for Value {
+    type Output = Result<Self, OpError>;
+    fn shl(self, rhs: Self) -> Self::Output {
+        match (self.as_integer(), rhs.as_integer()) {
+            (Some(_), Some(amount)) if amount < 0 => Err(OpError::Type),
+            (Some(0), Some(_)) => Ok(Value::Int(0)),
+            (Some(n), Some(amount)) if amount < 64 && (n << amount) >> amount == n => {
+                Ok(Value::Int(n << amount))
             }
-            _ => None,
+            (Some(_), Some(_)) => Err(OpError::Overflow),
+            _ => Err(OpError::Type),
+        }
+    }
+}
+
+// an arithmetic shift, so the sign is kept.
+impl Shr for Value {
+    type Output = Result<Self, OpError>;
+    fn shr(self, rhs: Self) -> Self::Output {
+        match (self.as_integer(), rhs.as_integer()) {
+            (Some(_), Some(amount)) if amount < 0 => Err(OpError::Type),
+            (Some(n), Some(amount)) => Ok(Value::Int(n >> amount.min(63))),
+            _ => Err(OpError::Type),
         }
     }
 }
@@ -93,9 +492,19 @@ impl Div for Value {
 impl PartialOrd for Value {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         match (self, other) {
-            (Value::Number(n1), Value::Number(n2)) => Some(n1.total_cmp(n2)),
+            (Value::Int(n1), Value::Int(n2)) => Some(n1.cmp(n2)),
             (Value::String(s1), Value::String(s2)) => Some(s1.len().cmp(&s2.len())),
-            _ => None,
+            // a Decimal is compared exactly, against a Number's exact binary value.
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+                match (self.as_exact_decimal(), other.as_exact_decimal()) {
+                    (Some(d1), Some(d2)) => Some(d1.cmp(&d2)),
+                    _ => None,
+                }
+            }
+            _ => match (self.as_f64(), other.as_f64()) {
+                (Some(n1), Some(n2)) => Some(n1.total_cmp(&n2)),
+                _ => None,
+            },
         }
     }
 }
@@ -103,10 +512,26 @@ impl PartialOrd for Value {
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Value::Int(n1), Value::Int(n2)) => n1 == n2,
             (Value::Number(n1), Value::Number(n2)) => n1 == n2,
+            // an Int equals the Number with the same value, so 1 == 1.0.
+            (Value::Int(n1), Value::Number(n2)) | (Value::Number(n2), Value::Int(n1)) => {
+                *n1 as f64 == *n2
+            }
+            (Value::Decimal(_), _) | (_, Value::Decimal(_)) => {
+                self.partial_cmp(other) == Some(std::cmp::Ordering::Equal)
+            }
             (Value::String(s1), Value::String(s2)) => s1 == s2,
             (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
             (Value::None, Value::None) => true,
+            // functions, Jungles and their instances are only equal to themselves.
+            (Value::Function(f1), Value::Function(f2)) => f1.same(f2),
+            (Value::Class(c1), Value::Class(c2)) => Rc::ptr_eq(c1, c2),
+            (Value::Instance(i1), Value::Instance(i2)) => Rc::ptr_eq(i1, i2),
+            // lists and maps compare by contents.
+            (Value::List(_), Value::List(_)) | (Value::Map(_), Value::Map(_)) => {
+                self.equals(other, &mut Vec::new())
+            }
             _ => false,
         }
     }
@@ -114,13 +539,39 @@ impl PartialEq for Value {
 
 #[cfg(test)]
 mod test {
-    use super::Value;
+    use super::{OpError, Value};
     #[test]
     fn test_neg() {
-        let value = Value::Number(5.0);
-        if let Some(val) = -value {
-            println!("{:?}", val);
-        }
+        assert_eq!(-Value::Number(5.0), Ok(Value::Number(-5.0)));
+        assert_eq!(-Value::Int(i64::MIN), Err(OpError::Overflow));
+    }
+
+    #[test]
+    fn test_int_arithmetic() {
+        assert!(matches!(Value::Int(7) + Value::Int(2), Ok(Value::Int(9))));
+        assert!(matches!(Value::Int(7) * Value::Number(0.5), Ok(Value::Number(n)) if n == 3.5));
+        assert!(matches!(Value::Int(7) / Value::Int(2), Ok(Value::Number(n)) if n == 3.5));
+        assert!(matches!(
+            Value::Int(-7).int_div(Value::Int(2)),
+            Ok(Value::Int(-3))
+        ));
+        assert!(matches!(
+            Value::Number(7.5).int_div(Value::Int(2)),
+            Ok(Value::Int(3))
+        ));
+        assert!(matches!(Value::Int(-7) % Value::Int(3), Ok(Value::Int(-1))));
+        assert_eq!(Value::Int(i64::MAX) + Value::Int(1), Err(OpError::Overflow));
+        assert_eq!(
+            Value::Int(i64::MIN).int_div(Value::Int(-1)),
+            Err(OpError::Overflow)
+        );
+        assert_eq!(Value::Int(1) % Value::Int(0), Err(OpError::DivisionByZero));
+        assert_eq!(
+            Value::Int(1) / Value::Number(0.0),
+            Err(OpError::DivisionByZero)
+        );
+        assert_eq!(Value::Int(1) - Value::Bool(true), Err(OpError::Type));
+        assert_eq!(Value::Int(1), Value::Number(1.0));
     }
 
     #[test]
@@ -129,6 +580,11 @@ mod test {
         let num = Value::Number(0.0);
         let bl = Value::Bool(true);
         let n = Value::None;
-        println!("{:?}", !n);
+        assert_eq!(!string_value, Value::Bool(true));
+        assert_eq!(!Value::String(String::from("LEMON")), Value::Bool(false));
+        assert_eq!(!num, Value::Bool(true));
+        assert_eq!(!Value::Number(2.5), Value::Bool(false));
+        assert_eq!(!bl, Value::Bool(false));
+        assert_eq!(!n, Value::Bool(true));
     }
 }
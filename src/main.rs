@@ -3,16 +3,18 @@ use goryl::interpreter;
 use std::env;
 use std::fs;
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args.len().cmp(&2) {
-        std::cmp::Ordering::Greater => println!("Usage: goryl <file>"),
-        std::cmp::Ordering::Equal => {
-            let content = fs::read_to_string(&args[1]);
-            match content {
-                Ok(code) => interpreter::run::run_file(code),
-                Err(e) => println!("Bug: Could not read file: {}", e),
-            }
+    let args: Vec<String> = env::args().skip(1).collect();
+    match interpreter::run::parse_options(&args) {
+        Err(e) => {
+            println!("{}", e);
+            println!("Usage: goryl [--scale digits] [--rounding mode] [file]");
         }
-        std::cmp::Ordering::Less => interpreter::run::run_prompt(),
+        Ok(options) => match options.file {
+            Some(file) => match fs::read_to_string(file) {
+                Ok(code) => interpreter::run::run_file(code, options.decimal_context),
+                Err(e) => println!("Bug: Could not read file: {}", e),
+            },
+            None => interpreter::run::run_prompt(options.decimal_context),
+        },
     }
 }
@@ -1,9 +1,21 @@
 use std::collections::HashMap;
 
 use super::token::Literal;
+use super::token::Span;
 use super::token::Token;
 use super::token::TokenType;
-use crate::errors::syntax_error::{SyntaxError, UnexpectedToken, UnterminatedString};
+use crate::errors::syntax_error::{
+    InvalidEscape, MalformedNumber, SyntaxError, UnexpectedToken, UnterminatedInterpolation,
+    UnterminatedString,
+};
+use crate::interpreter::decimal::Decimal;
+
+// an open ${ inside a string, depth counts the { } pairs opened inside the expression so the right } ends it.
+#[derive(Debug, Clone)]
+struct Interpolation {
+    depth: usize,
+    opening: Span, // the opening quote of the string, reported if the string never ends.
+}
 
 #[derive(Debug, Clone)]
 pub struct Scanner {
@@ -12,8 +24,12 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    column: usize, // the column of the character at current.
+    start_line: usize,
+    start_column: usize,
     pub errors: Vec<SyntaxError>,
     reserved_keywords: HashMap<String, TokenType>,
+    interpolations: Vec<Interpolation>,
 }
 
 impl Scanner {
@@ -36,27 +52,49 @@ impl Scanner {
         reserved_keywords.insert("let".to_string(), TokenType::Let);
         reserved_keywords.insert("while".to_string(), TokenType::While);
         reserved_keywords.insert("import".to_string(), TokenType::Import);
+        reserved_keywords.insert("break".to_string(), TokenType::Break);
+        reserved_keywords.insert("continue".to_string(), TokenType::Continue);
+        reserved_keywords.insert("try".to_string(), TokenType::Try);
+        reserved_keywords.insert("catch".to_string(), TokenType::Catch);
+        reserved_keywords.insert("finally".to_string(), TokenType::Finally);
+        reserved_keywords.insert("throw".to_string(), TokenType::Throw);
         Self {
             source,
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
             errors: Vec::new(),
             reserved_keywords,
+            interpolations: Vec::new(),
         }
     }
 
     pub fn scan_tokens(&mut self) {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column;
             self.scan_token()
         }
+        if let Some(interpolation) = self.interpolations.first() {
+            self.errors.push(SyntaxError::UnterminatedInterpolation(
+                UnterminatedInterpolation::new(interpolation.opening),
+            ));
+        }
         self.tokens.push(Token::new(
             TokenType::Eof,
             "".to_string(),
             Literal::None,
-            self.line,
+            Span {
+                offset: self.current,
+                length: 0,
+                line: self.line,
+                column: self.column,
+            },
         ));
     }
 
@@ -68,36 +106,125 @@ impl Scanner {
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(interpolation) = self.interpolations.last_mut() {
+                    interpolation.depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // closes the ${ so the rest of the string carries on from here.
+                Some(interpolation) if interpolation.depth == 0 => {
+                    let opening = interpolation.opening;
+                    self.interpolations.pop();
+                    self.handle_string(opening);
+                }
+                Some(interpolation) => {
+                    interpolation.depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            ':' => self.add_token(TokenType::Colon),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::Ellipsis)
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
+            '-' => {
+                if self.is_compound('=') {
+                    self.add_token(TokenType::MinusEqual)
+                } else if self.is_compound('-') {
+                    self.add_token(TokenType::MinusMinus)
+                } else {
+                    self.add_token(TokenType::Minus)
+                }
+            }
+            '+' => {
+                if self.is_compound('=') {
+                    self.add_token(TokenType::PlusEqual)
+                } else if self.is_compound('+') {
+                    self.add_token(TokenType::PlusPlus)
+                } else {
+                    self.add_token(TokenType::Plus)
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
-            '!' => match self.is_compound('=') {
-                true => self.add_token(TokenType::BangEqual),
-                false => self.add_token(TokenType::Bang),
+            '*' => {
+                if self.is_compound('*') {
+                    self.add_token(TokenType::StarStar)
+                } else if self.is_compound('=') {
+                    self.add_token(TokenType::StarEqual)
+                } else {
+                    self.add_token(TokenType::Star)
+                }
+            }
+            '%' => match self.is_compound('=') {
+                true => self.add_token(TokenType::PercentEqual),
+                false => self.add_token(TokenType::Percent),
             },
-            '=' => match self.is_compound('=') {
-                true => self.add_token(TokenType::EqualEqual),
-                false => self.add_token(TokenType::Equal),
+            '&' => self.add_token(TokenType::Ampersand),
+            '?' => match self.is_compound('?') {
+                true => self.add_token(TokenType::QuestionQuestion),
+                false => self.add_token(TokenType::Question),
             },
-            '<' => match self.is_compound('=') {
-                true => self.add_token(TokenType::LessEqual),
-                false => self.add_token(TokenType::Less),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '~' => match self.is_compound('/') {
+                true => self.add_token(TokenType::TildeSlash),
+                false => self
+                    .errors
+                    .push(SyntaxError::UnexpectedToken(UnexpectedToken::new(
+                        c,
+                        self.span(),
+                    ))),
             },
-            '>' => match self.is_compound('=') {
-                true => self.add_token(TokenType::GreaterEqual),
-                false => self.add_token(TokenType::Greater),
+            '!' => match self.is_compound('=') {
+                true => self.add_token(TokenType::BangEqual),
+                false => self.add_token(TokenType::Bang),
             },
+            '=' => {
+                if self.is_compound('=') {
+                    self.add_token(TokenType::EqualEqual)
+                } else if self.is_compound('>') {
+                    self.add_token(TokenType::Arrow)
+                } else {
+                    self.add_token(TokenType::Equal)
+                }
+            }
+            '<' => {
+                if self.is_compound('<') {
+                    self.add_token(TokenType::LessLess)
+                } else if self.is_compound('=') {
+                    self.add_token(TokenType::LessEqual)
+                } else {
+                    self.add_token(TokenType::Less)
+                }
+            }
+            '>' => {
+                if self.is_compound('>') {
+                    self.add_token(TokenType::GreaterGreater)
+                } else if self.is_compound('=') {
+                    self.add_token(TokenType::GreaterEqual)
+                } else {
+                    self.add_token(TokenType::Greater)
+                }
+            }
 
             '/' => {
                 if self.is_compound('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.is_compound('=') {
+                    self.add_token(TokenType::SlashEqual);
                 } else {
                     self.add_token(TokenType::Slash);
                 }
@@ -105,20 +232,20 @@ impl Scanner {
             ' ' => {}
             '\r' => {}
             '\t' => {}
-            '\n' => self.line += 1,
-            '\\' => {}
-            '"' => self.handle_string(),
+            '\n' => self.new_line(),
+            '"' => self.handle_string(self.span()),
 
             _ => {
                 // check if c is digit base 10, argument here is the radix.
-                if c.is_digit(10) {
+                if c.is_ascii_digit() {
                     self.handle_number();
                 } else if c.is_alphabetic() {
                     self.identifier();
                 } else {
                     self.errors
                         .push(SyntaxError::UnexpectedToken(UnexpectedToken::new(
-                            c, self.line,
+                            c,
+                            self.span(),
                         )));
                 }
             }
@@ -129,136 +256,469 @@ impl Scanner {
 
     fn add_token(&mut self, token_type: TokenType) {
         let lexeme = self.source[self.start..self.current].to_string();
-        let token = Token::new(token_type, lexeme, Literal::None, self.line);
+        let token = Token::new(token_type, lexeme, Literal::None, self.span());
         self.tokens.push(token);
     }
     fn add_literal(&mut self, token_type: TokenType, literal: Literal) {
         let lexeme = self.source[self.start..self.current].to_string();
-        let token = Token::new(token_type, lexeme, literal, self.line);
+        let token = Token::new(token_type, lexeme, literal, self.span());
         self.tokens.push(token);
     }
 
+    // the token scanned so far, from start up to current.
+    fn span(&self) -> Span {
+        Span {
+            offset: self.start,
+            length: self.current - self.start,
+            line: self.start_line,
+            column: self.start_column,
+        }
+    }
+
     fn is_at_end(&self) -> bool {
         self.current >= self.source.len()
     }
 
+    // current is a byte offset, so stepping over a character moves it by the character's utf-8 length.
     pub fn advance(&mut self) -> char {
         if self.is_at_end() {
             return '\0';
         }
-        let next_char = self
-            .source
-            .chars()
-            .nth(self.current)
-            .expect("Could not get next character");
-        self.current += 1;
+        let next_char = self.peek();
+        self.current += next_char.len_utf8();
+        self.column += 1;
 
         next_char
     }
     // used for checking if we have a compound lexeme like !=, ==, >=, ...
     fn is_compound(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        } else if self
-            .source
-            .chars()
-            .nth(self.current)
-            .expect("Could not get next character")
-            != expected
-        {
+        if self.is_at_end() || self.peek() != expected {
             return false;
         }
-        self.current += 1;
+        self.advance();
         true
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        } else {
-            return self
-                .source
-                .chars()
-                .nth(self.current)
-                .expect("Could not get nth character in from source");
-        }
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        return self
-            .source
-            .chars()
-            .nth(self.current + 1)
-            .expect("Could not get nth character in from source");
+        let mut rest = self.source[self.current..].chars();
+        rest.next();
+        rest.next().unwrap_or('\0')
     }
 
-    fn handle_string(&mut self) {
-        let error_line = self.line;
+    // scans from the opening quote, or from the } that closed an interpolation, up to the closing quote or the next ${.
+    // opening is the string's first quote, where an unterminated string is reported.
+    fn handle_string(&mut self, opening: Span) {
+        let mut literal_value = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            let c = self.advance();
+            match c {
+                '$' if self.peek() == '{' => {
+                    self.advance();
+                    self.add_literal(TokenType::Interpolation, Literal::String(literal_value));
+                    self.interpolations
+                        .push(Interpolation { depth: 0, opening });
+                    return;
+                }
+                '\\' => {
+                    if let Some(escaped) = self.escape_sequence() {
+                        literal_value.push(escaped);
+                    }
+                }
+                '\n' => {
+                    self.new_line();
+                    literal_value.push(c);
+                }
+                _ => literal_value.push(c),
             }
-            self.advance();
         }
 
         if self.is_at_end() {
             self.errors
                 .push(SyntaxError::UnterminatedString(UnterminatedString::new(
-                    error_line,
+                    opening,
                 )));
+            return;
         }
 
         self.advance();
-        let literal_value = self.source[(self.start + 1)..(self.current - 1)].to_string();
         self.add_literal(TokenType::String, Literal::String(literal_value));
     }
 
+    // called with the backslash consumed, unknown escapes are reported and dropped from the literal.
+    fn escape_sequence(&mut self) -> Option<char> {
+        let backslash = Span {
+            offset: self.current - 1,
+            length: 1,
+            line: self.line,
+            column: self.column - 1,
+        };
+        let c = self.advance();
+        let escaped = match c {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '$' => Some('$'), // lets a string contain a literal ${.
+            'u' => self.unicode_escape(),
+            _ => None,
+        };
+        if escaped.is_none() {
+            let span = Span {
+                length: self.current - backslash.offset,
+                ..backslash
+            };
+            let sequence = self.source[span.offset..self.current].to_string();
+            self.errors
+                .push(SyntaxError::InvalidEscape(InvalidEscape::new(
+                    sequence, span,
+                )));
+        }
+        escaped
+    }
+
+    // \u{XXXX}, between one and six hex digits naming a unicode scalar value.
+    fn unicode_escape(&mut self) -> Option<char> {
+        if !self.is_compound('{') {
+            return None;
+        }
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() && self.current - digits_start < 6 {
+            self.advance();
+        }
+        let digits = self.source[digits_start..self.current].to_string();
+        if digits.is_empty() || !self.is_compound('}') {
+            return None;
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 1;
+    }
+
     fn identifier(&mut self) {
         while self.peek().is_alphanumeric() {
             self.advance();
         }
         let source_text = self.source[self.start..self.current].to_string();
         match self.reserved_keywords.get(&source_text) {
-            Some(token_type) => self.add_token(token_type.clone()),
+            Some(token_type) => self.add_token(*token_type),
             None => self.add_token(TokenType::Identifier),
         }
     }
 
+    // scans greedily and validates afterwards, so 1__0 or 0b12 are reported as one malformed literal.
     fn handle_number(&mut self) {
-        // consume before decimal point
-        while self.peek().is_digit(10) {
-            self.advance();
-        }
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
-            // consume the decimal point
+        let first = self.source[self.start..self.current].to_string();
+        if first == "0" && matches!(self.peek(), 'x' | 'X' | 'o' | 'O' | 'b' | 'B') {
+            // consume the radix prefix
             self.advance();
-            // consume after decimal point
-            while self.peek().is_digit(10) {
+        } else {
+            // consume before decimal point
+            self.consume_digits();
+            if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+                // consume the decimal point
                 self.advance();
+                // consume after decimal point
+                self.consume_digits();
             }
+            if matches!(self.peek(), 'e' | 'E') {
+                self.advance();
+                if matches!(self.peek(), '+' | '-') {
+                    self.advance();
+                }
+            }
+        }
+        // anything still attached to the literal belongs to it, even though it makes it malformed.
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
+            self.advance();
+        }
+
+        let lexeme = self.source[self.start..self.current].to_string();
+        match parse_number(&lexeme) {
+            Some(literal) => self.add_literal(TokenType::Number, literal),
+            None => self
+                .errors
+                .push(SyntaxError::MalformedNumber(MalformedNumber::new(
+                    lexeme,
+                    self.span(),
+                ))),
+        }
+    }
+
+    fn consume_digits(&mut self) {
+        while self.peek().is_ascii_digit() || self.peek() == '_' {
+            self.advance();
         }
-        let literal_value: f64 = self.source[self.start..self.current]
-            .parse()
-            .expect("Could not parse to f64");
-        self.add_literal(TokenType::Number, Literal::Number(literal_value));
     }
 }
 
+// accepts 0x, 0o and 0b integers and decimals with an optional exponent, like 0xFF, 1_000 or 6.02e23.
+// literals without a decimal point or exponent are Ints, and have to fit in 64 bits. A d suffix makes a Decimal, like 19.99d.
+fn parse_number(text: &str) -> Option<Literal> {
+    let radix = match text.get(0..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        let digits = &text[2..];
+        if !is_digit_group(digits, radix) {
+            return None;
+        }
+        return i64::from_str_radix(&digits.replace('_', ""), radix)
+            .ok()
+            .map(Literal::Int);
+    }
+
+    let (text, is_decimal) = match text.strip_suffix('d') {
+        Some(text) => (text, true),
+        None => (text, false),
+    };
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], Some(&text[(i + 1)..])),
+        None => (text, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+    if !is_digit_group(integer, 10) || !fraction.is_none_or(|f| is_digit_group(f, 10)) {
+        return None;
+    }
+    if let Some(exponent) = exponent {
+        let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if !is_digit_group(digits, 10) {
+            return None;
+        }
+    }
+    let text = text.replace('_', "");
+    if is_decimal {
+        Decimal::parse(&text).map(Literal::Decimal)
+    } else if fraction.is_none() && exponent.is_none() {
+        text.parse().ok().map(Literal::Int)
+    } else {
+        text.parse().ok().map(Literal::Number)
+    }
+}
+
+// a non empty run of digits in the given radix, underscores may only sit between two digits.
+fn is_digit_group(digits: &str, radix: u32) -> bool {
+    !digits.is_empty()
+        && !digits.starts_with('_')
+        && !digits.ends_with('_')
+        && !digits.contains("__")
+        && digits.chars().all(|c| c == '_' || c.is_digit(radix))
+}
+
 #[cfg(test)]
 mod test {
     use super::Scanner;
+    use crate::errors::syntax_error::SyntaxError;
+    use crate::syntax::token::{Literal, TokenType};
 
     #[test]
     fn scanner_test() {
-        let source_code = String::from(
-            r#"
-            let lemin = \"LEMON\";\nprint lemin;\n
-                          "#,
+        let source_code = String::from("let lemin = \"LEMON\";\nprint lemin;\n");
+        let mut scanner = Scanner::new(source_code);
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        match &scanner.tokens[3].literal {
+            Literal::String(s) => assert_eq!(s, "LEMON"),
+            other => panic!("expected a string literal, got {:?}", other),
+        }
+        assert_eq!(scanner.tokens.last().unwrap().line, 3);
+    }
+
+    #[test]
+    fn unicode_test() {
+        let mut scanner = Scanner::new(String::from("let café = \"🦍 ${café}\";"));
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        assert_eq!(scanner.tokens[1].lexeme, "café");
+        match &scanner.tokens[3].literal {
+            Literal::String(s) => assert_eq!(s, "🦍 "),
+            other => panic!("expected a string literal, got {:?}", other),
+        }
+        assert_eq!(scanner.tokens[4].lexeme, "café");
+    }
+
+    #[test]
+    fn span_test() {
+        let mut scanner = Scanner::new(String::from("let é = \"a\nb\";\n  é += 1;"));
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        let positions: Vec<(usize, usize, usize, usize)> = scanner
+            .tokens
+            .iter()
+            .map(|t| (t.offset, t.length, t.line, t.column))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                (0, 3, 1, 1),   // let
+                (4, 2, 1, 5),   // é
+                (7, 1, 1, 7),   // =
+                (9, 5, 1, 9),   // the string, which starts on line 1
+                (14, 1, 2, 3),  // ;
+                (18, 2, 3, 3),  // é
+                (21, 2, 3, 5),  // +=
+                (24, 1, 3, 8),  // 1
+                (25, 1, 3, 9),  // ;
+                (26, 0, 3, 10), // end of file
+            ]
         );
+
+        let mut scanner = Scanner::new(String::from("let a = 1;\n  é + #"));
+        scanner.scan_tokens();
+        let span = scanner.errors[0].span();
+        assert_eq!(
+            (span.offset, span.length, span.line, span.column),
+            (18, 1, 2, 7)
+        );
+    }
+
+    #[test]
+    fn escape_test() {
+        let source_code = String::from(r#""a\tb\n\\ \"q\" \${x} \u{1F98D}\0""#);
         let mut scanner = Scanner::new(source_code);
         scanner.scan_tokens();
-        println!("{:#?}", scanner);
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        match &scanner.tokens[0].literal {
+            Literal::String(s) => assert_eq!(s, "a\tb\n\\ \"q\" ${x} \u{1F98D}\0"),
+            other => panic!("expected a string literal, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn invalid_escape_test() {
+        let mut scanner = Scanner::new(String::from("\n  \"bad \\q\" \\"));
+        scanner.scan_tokens();
+        match &scanner.errors[..] {
+            [e @ SyntaxError::InvalidEscape(_), u @ SyntaxError::UnexpectedToken(_)] => {
+                assert_eq!(
+                    e.message(),
+                    "Invalid escape sequence \\q on line 2 column 8"
+                );
+                assert_eq!(u.message(), "Unexpected token \\ on line 2");
+                assert_eq!((u.span().line, u.span().column), (2, 12));
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn interpolation_test() {
+        let mut scanner = Scanner::new(String::from(r#""a ${x + "}"} b ${ {} } c""#));
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        let types: Vec<TokenType> = scanner.tokens.iter().map(|t| t.token_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                TokenType::Interpolation,
+                TokenType::Identifier,
+                TokenType::Plus,
+                TokenType::String,
+                TokenType::Interpolation,
+                TokenType::LeftBrace,
+                TokenType::RightBrace,
+                TokenType::String,
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_interpolation_test() {
+        let mut scanner = Scanner::new(String::from("\n\"total ${amount"));
+        scanner.scan_tokens();
+        match &scanner.errors[..] {
+            [e @ SyntaxError::UnterminatedInterpolation(_)] => {
+                assert_eq!(e.message(), "Unterminated string interpolation on line 2");
+                assert_eq!(e.span().line, 2);
+            }
+            errors => panic!("unexpected errors {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn number_test() {
+        let mut scanner = Scanner::new(String::from(
+            "123 1.5 0xFF 0b1010 0o17 1_000_000 6.02e23 1e-3 2E+2 1.foo",
+        ));
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        let ints: Vec<i64> = scanner
+            .tokens
+            .iter()
+            .filter_map(|t| match t.literal {
+                Literal::Int(n) => Some(n),
+                _ => None,
+            })
+            .collect();
+        let numbers: Vec<f64> = scanner
+            .tokens
+            .iter()
+            .filter_map(|t| match t.literal {
+                Literal::Number(n) => Some(n),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ints, vec![123, 255, 10, 15, 1_000_000, 1]);
+        assert_eq!(numbers, vec![1.5, 6.02e23, 1e-3, 200.0]);
+
+        let mut scanner = Scanner::new(String::from("19.99d 1_000d 2.5e-1d 0x1d"));
+        scanner.scan_tokens();
+        assert!(scanner.errors.is_empty(), "{:?}", scanner.errors);
+        let literals: Vec<String> = scanner
+            .tokens
+            .iter()
+            .filter_map(|t| match &t.literal {
+                Literal::Decimal(d) => Some(d.to_string()),
+                Literal::Int(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(literals, vec!["19.99", "1000", "0.25", "29"]);
+    }
+
+    #[test]
+    fn malformed_number_test() {
+        for source in [
+            "1__0",
+            "1_",
+            "0x",
+            "0b102",
+            "0xG1",
+            "1e",
+            "1.5e+",
+            "12abc",
+            "1_.5",
+            "9223372036854775808",
+            "1dd",
+            "1e99999d",
+        ] {
+            let mut scanner = Scanner::new(source.to_string());
+            scanner.scan_tokens();
+            match &scanner.errors[..] {
+                [e @ SyntaxError::MalformedNumber(_)] => {
+                    assert_eq!(
+                        e.message(),
+                        format!("Malformed number {} on line 1", source)
+                    );
+                    assert_eq!(e.span().length, source.len());
+                }
+                errors => panic!("unexpected errors for {}: {:?}", source, errors),
+            }
+        }
     }
 }
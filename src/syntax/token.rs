@@ -1,3 +1,5 @@
+use crate::interpreter::decimal::Decimal;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenType {
     // Single-character tokens.
@@ -5,13 +7,21 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
+    Colon,
+    Question,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
 
     // One or two character tokens.
     Bang,
@@ -22,11 +32,26 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    TildeSlash, // ~/ divides and truncates towards zero.
+    StarStar,
+    LessLess,
+    GreaterGreater,
+    PlusEqual,
+    MinusEqual,
+    PlusPlus,
+    MinusMinus,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    QuestionQuestion,
+    Arrow,
+    Ellipsis,
 
     // Literals.
     Identifier,
     String,
     Number,
+    Interpolation, // the string fragment before a ${, the embedded expression's tokens follow it.
 
     // Keywords.
     And,
@@ -46,6 +71,12 @@ pub enum TokenType {
     Let,
     While,
     Import,
+    Break,
+    Continue,
+    Try,
+    Catch,
+    Finally,
+    Throw,
 
     Eof,
 }
@@ -53,26 +84,63 @@ pub enum TokenType {
 #[derive(Debug, Clone)]
 pub enum Literal {
     String(String),
+    Int(i64),
     Number(f64),
+    Decimal(Decimal),
     Bool(bool),
     None,
 }
 
+// a stretch of source, offset and length are in bytes. line and column count from 1, and column counts characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    // from the start of this span to the end of the other one.
+    pub fn to(self, end: Span) -> Span {
+        let end_offset = (end.offset + end.length).max(self.offset + self.length);
+        Span {
+            length: end_offset - self.offset,
+            ..self
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Literal,
     pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub length: usize,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Literal, line: usize) -> Self {
+    pub fn new(token_type: TokenType, lexeme: String, literal: Literal, span: Span) -> Self {
         Self {
             token_type,
             lexeme,
             literal,
-            line,
+            line: span.line,
+            column: span.column,
+            offset: span.offset,
+            length: span.length,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        Span {
+            offset: self.offset,
+            length: self.length,
+            line: self.line,
+            column: self.column,
         }
     }
 }